@@ -6,10 +6,20 @@ mod utils;
 
 use clap::{Parser, Subcommand};
 use colored::Colorize;
-use providers::get_all_providers;
-use std::path::Path;
-use types::{OutputFormat, ProviderResult};
-use utils::format::{format_csv, format_json, format_table, print_banner, print_doctor_results};
+use providers::{builtin_providers, get_all_providers, AnthropicApiProvider, ClaudeCodeProvider, Provider};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use types::{OutputFormat, ProviderResult, TimeRange, UsageRecord};
+use utils::allocation::{AllocationRules, COST_CENTER};
+use utils::config::{self, Config};
+use utils::format::{
+    format_cost_centers_table, format_csv, format_git_trailers, format_json, format_rate_limits_table, format_repos_csv, format_repos_json, format_repos_table, format_table,
+    format_tools_csv, format_tools_json, format_tools_table, print_banner, print_doctor_results,
+};
+
+/// Per-provider timeout when neither `--timeout` nor the config file sets one
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
 
 #[derive(Parser)]
 #[command(name = "a2zusage")]
@@ -22,13 +32,49 @@ struct Cli {
     #[arg(short, long)]
     tool: Option<String>,
 
-    /// Output format
-    #[arg(short, long, value_enum, default_value = "table")]
-    format: OutputFormat,
+    /// Output format [default: table]
+    #[arg(short, long, value_enum, global = true)]
+    format: Option<OutputFormat>,
 
     /// Show verbose output
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     verbose: bool,
+
+    /// Show per-provider sub-breakdowns (e.g., Claude Code by entrypoint) in the table
+    #[arg(short, long)]
+    breakdown: bool,
+
+    /// Per-provider timeout in seconds; slow providers are reported as Timeout [default: 30]
+    #[arg(long, global = true)]
+    timeout: Option<u64>,
+
+    /// Cost allocation rules file (default: ~/.config/a2zusage/cost-centers.toml if present)
+    #[arg(long, global = true)]
+    rules: Option<PathBuf>,
+
+    /// Config file (default: ~/.config/a2zusage/config.toml if present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Also scan this directory as a home root (devcontainer, WSL mount, backup); repeatable
+    #[arg(long = "home", value_name = "DIR", global = true)]
+    homes: Vec<PathBuf>,
+}
+
+impl Cli {
+    /// Output format from the flag, else the config file, else table
+    fn format(&self) -> OutputFormat {
+        self.format.or(config::get().format).unwrap_or_default()
+    }
+
+    /// Timeout for one provider: the flag, else its config override, else the config default
+    fn timeout(&self, provider: &str) -> Duration {
+        Duration::from_secs(self.timeout.or(config::get().timeout(provider)).unwrap_or(DEFAULT_TIMEOUT_SECS))
+    }
+
+    fn breakdown(&self) -> bool {
+        self.breakdown || config::get().breakdown.unwrap_or(false)
+    }
 }
 
 #[derive(Subcommand)]
@@ -37,61 +83,209 @@ enum Commands {
     Doctor,
     /// List all supported tools
     List,
+    /// Claude Code tool-call counts and subagent usage share
+    Tools,
+    /// Usage and cost per git repository and branch
+    Repos,
+    /// Append AI usage trailers to a commit message (for use as a prepare-commit-msg hook)
+    GitTrailer {
+        /// Commit message file; trailers are printed to stdout when omitted
+        msg_file: Option<PathBuf>,
+        /// Commit message source passed by git (message, template, merge, squash, commit)
+        source: Option<String>,
+        /// Commit SHA passed by git for amends
+        sha: Option<String>,
+    },
+    /// Inspect the configuration file
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
-    let cli = Cli::parse();
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Print the effective configuration
+    Show,
+    /// Check the configuration file for errors
+    Validate,
+    /// Print the configuration file location
+    Path,
+}
 
-    match cli.command {
-        Some(Commands::Doctor) => run_doctor().await,
+fn main() -> anyhow::Result<()> {
+    let runtime = tokio::runtime::Builder::new_multi_thread().enable_all().build()?;
+    let result = runtime.block_on(run(Cli::parse()));
+    // A provider that timed out may still be stuck in a blocking scan; don't wait for it
+    runtime.shutdown_background();
+    result
+}
+
+async fn run(cli: Cli) -> anyhow::Result<()> {
+    // The config subcommands load (and report problems with) the file themselves
+    if !matches!(cli.command, Some(Commands::Config { .. })) {
+        let config = match Config::load(cli.config.as_deref()) {
+            Ok(config) => config,
+            // A commit hook must never block the commit
+            Err(e) if matches!(cli.command, Some(Commands::GitTrailer { .. })) => {
+                eprintln!("a2zusage: using the default configuration: {:#}", e);
+                Config::default()
+            }
+            Err(e) => return Err(e),
+        };
+        if config.color == Some(false) {
+            colored::control::set_override(false);
+        }
+        config::init(config);
+    }
+
+    match &cli.command {
+        Some(Commands::Doctor) => run_doctor(&cli).await,
         Some(Commands::List) => run_list(),
+        Some(Commands::Tools) => run_tools(&cli),
+        Some(Commands::Repos) => run_repos(&cli).await,
+        Some(Commands::GitTrailer { msg_file, source, .. }) => {
+            if let Err(e) = run_git_trailer(&cli, msg_file.as_deref(), source.as_deref()).await {
+                eprintln!("a2zusage git-trailer: {:#}", e);
+            }
+            Ok(())
+        }
+        Some(Commands::Config { action }) => run_config(&cli, action),
         None => run_usage_query(&cli).await,
     }
 }
 
-async fn run_usage_query(cli: &Cli) -> anyhow::Result<()> {
-    // Only show banner for table format
-    if matches!(cli.format, OutputFormat::Table) {
-        print_banner();
-    }
-
+/// All providers, filtered by --tool if specified
+fn selected_providers(cli: &Cli) -> Vec<Arc<dyn Provider>> {
     let providers = get_all_providers();
 
-    // Filter by tool if specified
-    let providers: Vec<_> = if let Some(ref tool_filter) = cli.tool {
+    if let Some(ref tool_filter) = cli.tool {
         providers
             .into_iter()
             .filter(|p| {
                 p.name().contains(tool_filter) || p.display_name().to_lowercase().contains(&tool_filter.to_lowercase())
             })
+            .map(Arc::from)
             .collect()
     } else {
-        providers
+        providers.into_iter().map(Arc::from).collect()
+    }
+}
+
+/// Home roots a provider should be queried under: the real home (None),
+/// plus each alternative root for providers that read local files
+fn roots_for(provider: &dyn Provider, homes: &[PathBuf]) -> Vec<Option<PathBuf>> {
+    let mut roots = vec![None];
+    if provider.data_source_type().is_local() {
+        roots.extend(homes.iter().cloned().map(Some));
+    }
+    roots
+}
+
+/// Query one provider under one home root on its own task, so a hung one can be abandoned.
+/// With `with_records`, the provider's usage records are collected in the same scan.
+async fn query_provider(
+    provider: &Arc<dyn Provider>,
+    home: Option<PathBuf>,
+    timeout: Duration,
+    with_records: bool,
+) -> (ProviderResult, Vec<UsageRecord>) {
+    let p = Arc::clone(provider);
+    let blocking = provider.data_source_type().is_local();
+    let mut handle = utils::homes::spawn_in(home, blocking, async move {
+        if with_records {
+            p.get_usage_with_records().await
+        } else {
+            p.get_usage(None).await.map(|result| (result, Vec::new()))
+        }
+    });
+    let result = match tokio::time::timeout(timeout, &mut handle).await {
+        Ok(Ok(Ok(queried))) => return queried,
+        Ok(Ok(Err(e))) => ProviderResult::error(provider.name(), provider.display_name(), &e.to_string()),
+        Ok(Err(e)) => ProviderResult::error(provider.name(), provider.display_name(), &e.to_string()),
+        Err(_) => {
+            handle.abort();
+            ProviderResult::timeout(provider.name(), provider.display_name(), timeout)
+        }
     };
+    (result, Vec::new())
+}
+
+/// Collect attributed usage records from all providers (and home roots) in parallel.
+/// Providers that fail or exceed the timeout contribute nothing.
+async fn collect_usage_records(
+    providers: &[Arc<dyn Provider>],
+    homes: &[PathBuf],
+    timeout: impl Fn(&str) -> Duration,
+) -> Vec<UsageRecord> {
+    let queries = providers.iter().filter(|p| p.has_usage_records()).flat_map(|p| {
+        let timeout = timeout(p.name());
+        roots_for(p.as_ref(), homes).into_iter().map(move |home| async move {
+            let provider = Arc::clone(p);
+            let blocking = provider.data_source_type().is_local();
+            let mut handle = utils::homes::spawn_in(home, blocking, async move { provider.get_usage_records().await });
+            match tokio::time::timeout(timeout, &mut handle).await {
+                Ok(Ok(Ok(records))) => records,
+                Ok(_) => Vec::new(),
+                Err(_) => {
+                    handle.abort();
+                    Vec::new()
+                }
+            }
+        })
+    });
+    let batches = futures::future::join_all(queries).await;
+
+    batches.into_iter().flatten().collect()
+}
+
+async fn run_usage_query(cli: &Cli) -> anyhow::Result<()> {
+    // Only show banner for table format
+    if matches!(cli.format(), OutputFormat::Table) {
+        print_banner();
+    }
+
+    let providers = selected_providers(cli);
 
     if providers.is_empty() {
         println!("{}", "No matching providers found.".yellow());
         return Ok(());
     }
 
+    let rules = AllocationRules::load(cli.rules.as_deref())?;
+
     // Query all providers in parallel
-    let show_progress = matches!(cli.format, OutputFormat::Table);
+    let show_progress = matches!(cli.format(), OutputFormat::Table);
     if show_progress {
         println!("{}", "Scanning AI tools...".dimmed());
     }
 
-    let results: Vec<ProviderResult> = futures::future::join_all(
-        providers.iter().map(|p| async {
+    // Each provider runs on its own task per home root so a hung one can be cancelled
+    // without holding back the rest of the report.
+    let homes = utils::homes::alternate_roots(&cli.homes);
+    let homes = &homes;
+    // Cost allocation needs the usage records, which providers collect in the same scan
+    let with_records = rules.is_some();
+    let queried: Vec<(ProviderResult, Vec<UsageRecord>)> = futures::future::join_all(
+        providers.iter().map(|p| async move {
             if cli.verbose && show_progress {
                 println!("  Checking {}...", p.display_name());
             }
-            match p.get_usage(None).await {
-                Ok(result) => result,
-                Err(e) => ProviderResult::error(p.name(), p.display_name(), &e.to_string()),
-            }
+            let roots = roots_for(p.as_ref(), homes);
+            let per_root = futures::future::join_all(
+                roots.iter().map(|home| query_provider(p, home.clone(), cli.timeout(p.name()), with_records && p.has_usage_records()))
+            ).await;
+            let (per_root, records): (Vec<ProviderResult>, Vec<Vec<UsageRecord>>) = per_root.into_iter().unzip();
+            (utils::homes::merge(roots.into_iter().zip(per_root).collect()), records.concat())
         })
     ).await;
+    let (mut results, records): (Vec<ProviderResult>, Vec<Vec<UsageRecord>>) = queried.into_iter().unzip();
+
+    // Split each provider's usage into cost centers when allocation rules are configured
+    if let Some(mut rules) = rules {
+        rules.apply(&mut results, &records.concat());
+    }
+    let has_cost_centers = results.iter().any(|r| r.breakdown.iter().any(|b| b.dimension == COST_CENTER));
 
     // Clear the "Scanning" line (only for table format)
     if show_progress {
@@ -99,14 +293,24 @@ async fn run_usage_query(cli: &Cli) -> anyhow::Result<()> {
     }
 
     // Format and display output
-    let output = match cli.format {
-        OutputFormat::Table => format_table(&results),
+    let output = match cli.format() {
+        OutputFormat::Table => format_table(&results, cli.breakdown()),
         OutputFormat::Json => format_json(&results),
         OutputFormat::Csv => format_csv(&results),
     };
 
     println!("{}", output);
 
+    if has_cost_centers && matches!(cli.format(), OutputFormat::Table) {
+        println!("\n{}", "Cost Centers:".bold());
+        println!("{}", format_cost_centers_table(&results));
+    }
+
+    if results.iter().any(|r| !r.rate_limits.is_empty()) && matches!(cli.format(), OutputFormat::Table) {
+        println!("\n{}", "Rate Limits:".bold());
+        println!("{}", format_rate_limits_table(&results));
+    }
+
     // Show verbose info if requested
     if cli.verbose {
         println!("\n{}", "Data Sources:".bold());
@@ -115,6 +319,7 @@ async fn run_usage_query(cli: &Cli) -> anyhow::Result<()> {
                 let status_icon = match result.status {
                     types::ProviderStatus::Active => "✓".green(),
                     types::ProviderStatus::Unsupported => "~".yellow(),
+                    types::ProviderStatus::Timeout => "-".yellow(),
                     _ => "○".dimmed(),
                 };
 
@@ -124,21 +329,31 @@ async fn run_usage_query(cli: &Cli) -> anyhow::Result<()> {
                     source.dimmed()
                 );
             }
+            for note in &result.notes {
+                println!("      {}", note.dimmed());
+            }
         }
     }
 
     Ok(())
 }
 
-async fn run_doctor() -> anyhow::Result<()> {
+async fn run_doctor(cli: &Cli) -> anyhow::Result<()> {
     print_banner();
     println!("{}\n", "Running diagnostics...".cyan());
 
     let providers = get_all_providers();
+    let homes = utils::homes::alternate_roots(&cli.homes);
     let mut checks: Vec<(String, String, bool)> = Vec::new();
 
     for provider in &providers {
-        let paths = provider.get_paths_to_check();
+        let paths: Vec<String> = roots_for(provider.as_ref(), &homes)
+            .into_iter()
+            .flat_map(|home| match home {
+                Some(home) => utils::paths::with_home_sync(home, || provider.get_paths_to_check()),
+                None => provider.get_paths_to_check(),
+            })
+            .collect();
         for path in paths {
             if path.is_empty() {
                 continue;
@@ -147,7 +362,11 @@ async fn run_doctor() -> anyhow::Result<()> {
             let exists = if path.starts_with("http") || path.contains("environment variable") {
                 // For URLs and env vars, check differently
                 if path.contains("environment variable") {
-                    false // Can't easily check
+                    // "NAME environment variable": found when set
+                    path.split_whitespace()
+                        .next()
+                        .and_then(std::env::var_os)
+                        .is_some_and(|v| !v.is_empty())
                 } else {
                     true // URLs are always "found"
                 }
@@ -173,15 +392,193 @@ async fn run_doctor() -> anyhow::Result<()> {
     );
 
     // Environment variable hints
-    println!("{}", "Environment Variables for API Providers:".bold());
+    println!("{}", "Environment Variables:".bold());
+    println!("  {} - Claude Code config roots (comma-separated)", "CLAUDE_CONFIG_DIR".cyan());
     println!("  {} - GitHub Copilot", "GITHUB_TOKEN".cyan());
     println!("  {} - OpenAI Codex", "OPENAI_API_KEY".cyan());
+    println!("  {} - Codex CLI home directory", "CODEX_HOME".cyan());
+    println!("  {} - Aider analytics log file", "AIDER_ANALYTICS_LOG".cyan());
+    println!("  {} - Continue global directory", "CONTINUE_GLOBAL_DIR".cyan());
+    println!("  {} - llm CLI user directory", "LLM_USER_PATH".cyan());
+    println!(
+        "  {} - Anthropic Admin API ({})",
+        "ANTHROPIC_ADMIN_KEY".cyan(),
+        AnthropicApiProvider::admin_key_status()
+    );
+    println!("  {} - OpenRouter spend and credit", "OPENROUTER_API_KEY".cyan());
+    println!("  {} - DeepSeek balance", "DEEPSEEK_API_KEY".cyan());
     println!("  {} - AWS credentials for Amazon Q", "AWS_PROFILE".cyan());
     println!();
 
     Ok(())
 }
 
+fn run_tools(cli: &Cli) -> anyhow::Result<()> {
+    if matches!(cli.format(), OutputFormat::Table) {
+        print_banner();
+    }
+
+    let analytics = match ClaudeCodeProvider::tool_analytics(&utils::homes::alternate_roots(&cli.homes)) {
+        Some(a) => a,
+        None => {
+            println!("{}", "No Claude Code data found.".yellow());
+            return Ok(());
+        }
+    };
+
+    let output = match cli.format() {
+        OutputFormat::Table => format_tools_table(&analytics),
+        OutputFormat::Json => format_tools_json(&analytics),
+        OutputFormat::Csv => format_tools_csv(&analytics),
+    };
+    println!("{}", output);
+
+    if cli.verbose {
+        println!("\n{} {}", "Data Sources:".bold(), analytics.data_source.dimmed());
+    }
+
+    Ok(())
+}
+
+async fn run_repos(cli: &Cli) -> anyhow::Result<()> {
+    if matches!(cli.format(), OutputFormat::Table) {
+        print_banner();
+    }
+
+    let providers = selected_providers(cli);
+    let records = collect_usage_records(&providers, &utils::homes::alternate_roots(&cli.homes), |name| cli.timeout(name)).await;
+    let mut rules = AllocationRules::load(cli.rules.as_deref())?;
+    let repos = utils::git::group_by_repo(&records, rules.as_mut());
+
+    if repos.is_empty() {
+        println!("{}", "No usage attributed to a git repository.".yellow());
+        return Ok(());
+    }
+
+    let output = match cli.format() {
+        OutputFormat::Table => format_repos_table(&repos),
+        OutputFormat::Json => format_repos_json(&repos),
+        OutputFormat::Csv => format_repos_csv(&repos),
+    };
+    println!("{}", output);
+
+    if matches!(cli.format(), OutputFormat::Table) {
+        let unattributed: Vec<&str> = providers
+            .iter()
+            .filter(|p| !p.has_usage_records())
+            .map(|p| p.display_name())
+            .collect();
+        if !unattributed.is_empty() {
+            println!(
+                "{}",
+                format!("Not included (no working directory in their data): {}", unattributed.join(", ")).dimmed()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+async fn run_git_trailer(cli: &Cli, msg_file: Option<&Path>, source: Option<&str>) -> anyhow::Result<()> {
+    // Merges, squashes and amends/reuses (-c/-C/--amend) don't represent new AI-assisted work
+    if matches!(source, Some("merge") | Some("squash") | Some("commit")) {
+        return Ok(());
+    }
+
+    let cwd = std::env::current_dir()?;
+    let repo_root = match utils::git::find_repo_root(&cwd) {
+        Some(root) => root,
+        None => return Ok(()),
+    };
+
+    // Everything since the previous commit (or all history for the first commit)
+    let range = TimeRange {
+        start: utils::git::last_commit_time(&repo_root).unwrap_or(chrono::DateTime::UNIX_EPOCH),
+        end: chrono::Utc::now(),
+    };
+
+    let providers = selected_providers(cli);
+    let records = collect_usage_records(&providers, &utils::homes::alternate_roots(&cli.homes), |name| cli.timeout(name)).await;
+    let (usage, tools) = utils::git::usage_in_repo(&records, &repo_root, &range);
+
+    if usage.total_tokens() == 0 {
+        return Ok(());
+    }
+
+    let trailers = format_git_trailers(&usage, &tools);
+    match msg_file {
+        Some(path) => utils::git::append_trailers(path, &trailers)?,
+        None => {
+            for (key, value) in &trailers {
+                println!("{}: {}", key, value);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_config(cli: &Cli, action: &ConfigAction) -> anyhow::Result<()> {
+    let path = cli.config.clone().or_else(utils::paths::a2zusage::config_file);
+    let exists = path.as_ref().map(|p| p.exists()).unwrap_or(false);
+
+    match action {
+        ConfigAction::Path => match path {
+            Some(path) => println!("{}", path.display()),
+            None => anyhow::bail!("could not determine the config directory"),
+        },
+        ConfigAction::Validate => {
+            let config = Config::load(cli.config.as_deref())?;
+            let Some(path) = path.filter(|_| exists) else {
+                println!("No config file found; using defaults.");
+                return Ok(());
+            };
+
+            let known: Vec<&'static str> = builtin_providers().iter().map(|p| p.name()).collect();
+            let unknown: Vec<&str> = config.providers.keys()
+                .map(String::as_str)
+                .filter(|name| !known.contains(name))
+                .collect();
+            if !unknown.is_empty() {
+                anyhow::bail!(
+                    "unknown provider(s) in {}: {} (see `a2zusage list` for IDs)",
+                    path.display(),
+                    unknown.join(", ")
+                );
+            }
+
+            println!("{} {} is valid", "✓".green(), path.display());
+        }
+        ConfigAction::Show => {
+            let mut config = Config::load(cli.config.as_deref())?;
+            // Never echo secrets
+            for provider in config.providers.values_mut() {
+                if provider.api_key.is_some() {
+                    provider.api_key = Some("********".to_string());
+                }
+            }
+            // Fill in the built-in defaults so the output shows what a run would use
+            let effective = Config {
+                format: Some(config.format.unwrap_or_default()),
+                timezone: Some(config.timezone.unwrap_or_else(|| "local".to_string())),
+                timeout: Some(config.timeout.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+                breakdown: Some(config.breakdown.unwrap_or(false)),
+                color: Some(config.color.unwrap_or(true)),
+                homes: config.homes,
+                providers: config.providers,
+            };
+
+            match path.filter(|_| exists) {
+                Some(path) => println!("# {}", path.display()),
+                None => println!("# No config file found; showing defaults"),
+            }
+            print!("{}", toml::to_string_pretty(&effective)?);
+        }
+    }
+
+    Ok(())
+}
+
 fn run_list() -> anyhow::Result<()> {
     print_banner();
     println!("{}\n", "Supported AI Coding Tools:".bold());
@@ -190,12 +587,22 @@ fn run_list() -> anyhow::Result<()> {
         ("claude-code", "Claude Code", "CLI + IDE extension (shared JSONL)"),
         ("cursor", "Cursor", "SQLite database"),
         ("github-copilot", "GitHub Copilot", "API + Local logs"),
-        ("cline", "Cline", "VS Code extension storage"),
+        ("cline", "Cline", "VS Code extension storage (Cline, Roo Code, Kilo Code)"),
         ("windsurf", "Windsurf", "Cascade logs"),
         ("warp", "Warp AI", "SQLite database"),
         ("opencode", "OpenCode", "Local JSON files"),
         ("openai-codex", "OpenAI Codex", "OpenAI API"),
+        ("codex-cli", "Codex CLI", "Local session rollouts"),
+        ("anthropic-api", "Anthropic API", "Anthropic Admin API"),
+        ("openrouter", "OpenRouter", "Key spend + prepaid credit API"),
+        ("deepseek", "DeepSeek", "Prepaid balance API"),
+        ("litellm", "LiteLLM", "Proxy spend logs API (configured URL)"),
         ("gemini-cli", "Gemini CLI", "Local telemetry"),
+        ("aider", "Aider", "Analytics log or chat histories"),
+        ("continue", "Continue", "Local dev data (tokensGenerated)"),
+        ("zed", "Zed", "Agent threads database"),
+        ("goose", "Goose", "Local session JSONL"),
+        ("llm", "llm CLI", "SQLite logs database"),
         ("amazon-q", "Amazon Q", "Local logs"),
         ("tabnine", "Tabnine", "Local logs"),
         ("sourcegraph-cody", "Sourcegraph Cody", "VS Code extension"),
@@ -212,6 +619,10 @@ fn run_list() -> anyhow::Result<()> {
     println!("  a2zusage -t cursor    # Query specific tool");
     println!("  a2zusage -f json      # Output as JSON");
     println!("  a2zusage doctor       # Check configuration");
+    println!("  a2zusage tools        # Claude Code tool and subagent analytics");
+    println!("  a2zusage repos        # Usage and cost per git repository and branch");
+    println!("  a2zusage git-trailer  # AI usage trailers for the next commit");
+    println!("  a2zusage config show  # Effective configuration");
     println!();
 
     Ok(())
@@ -0,0 +1,422 @@
+//! Aider Provider
+//! Reads Aider's analytics log (JSONL, one event per line) when one is configured through
+//! `analytics-log:` in ~/.aider.conf.yml or `AIDER_ANALYTICS_LOG`.
+//!
+//! Without an analytics log, falls back to the `.aider.chat.history.md` transcripts Aider
+//! writes into each repository, which record a "Tokens: … Cost: …" line after every reply.
+//! Replies carry no time of their own: they are dated by their session's start, except in the
+//! last session, which is dated by the file's last write so a session still open counts as recent.
+
+use super::Provider;
+use crate::types::{ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, aider};
+use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost_with_cache;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use regex_lite::Regex;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// One analytics event
+#[derive(Debug, Deserialize)]
+struct AnalyticsEvent {
+    event: Option<String>,
+    #[serde(default)]
+    properties: EventProperties,
+    /// Seconds since the epoch
+    time: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct EventProperties {
+    main_model: Option<String>,
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+    cost: Option<f64>,
+}
+
+/// `# aider chat started at 2025-01-31 14:05:12` (local time)
+static SESSION_HEADER: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^# aider chat started at (\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2})").unwrap());
+
+/// `> Model: claude-3-5-sonnet-20241022 with diff edit format` or `> Main model: …`
+static MODEL_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^> (?:Main model|Model): (\S+)").unwrap());
+
+/// `> Tokens: 2.3k sent, 1.1k cache write, 4.0k cache hit, 512 received. Cost: $0.01 message, $0.05 session.`
+static TOKENS_LINE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"^> Tokens: ([\d.,]+[kKmM]?) sent(?:, ([\d.,]+[kKmM]?) cache write)?(?:, ([\d.,]+[kKmM]?) cache hit)?, ([\d.,]+[kKmM]?) received\.(?: Cost: \$([\d.,]+) (?:message|request))?",
+    )
+    .unwrap()
+});
+
+/// Receives the usage, time and model of each reply
+type ReplySink<'a> = dyn FnMut(UsageData, Option<DateTime<Utc>>, Option<&str>) + 'a;
+
+pub struct AiderProvider;
+
+/// Per-model and per-repository slices
+#[derive(Default)]
+struct Breakdowns {
+    by_model: HashMap<String, UsageStats>,
+    by_repo: HashMap<String, UsageStats>,
+}
+
+impl AiderProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Analytics log from the environment, else from `analytics-log:` in ~/.aider.conf.yml
+    fn analytics_log() -> Option<PathBuf> {
+        aider::analytics_log_from_env().or_else(|| {
+            let contents = fs::read_to_string(aider::config_file()?).ok()?;
+            Self::analytics_log_from_config(&contents)
+        })
+    }
+
+    /// Pick the `analytics-log` key out of Aider's YAML config; it is a flat key/value file
+    fn analytics_log_from_config(contents: &str) -> Option<PathBuf> {
+        let value = contents
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix("analytics-log:"))
+            .map(|value| value.split(" #").next().unwrap_or(value).trim().trim_matches(|c| c == '"' || c == '\''))
+            .next_back()
+            .filter(|value| !value.is_empty())?;
+        Some(PathBuf::from(paths::expand_home(value)))
+    }
+
+    /// Chat history files, including configured repositories (or history files)
+    fn history_files() -> Vec<PathBuf> {
+        // A configured repository and the default for the current one can be the same file
+        let mut seen = HashSet::new();
+        config::get()
+            .data_paths("aider", aider::history_files())
+            .into_iter()
+            .map(|p| if p.is_dir() { p.join(aider::HISTORY_FILE) } else { p })
+            .filter(|p| seen.insert(p.canonicalize().unwrap_or_else(|_| p.clone())))
+            .collect()
+    }
+
+    fn process_analytics_log(
+        path: &Path,
+        stats: &mut UsageStats,
+        breakdowns: &mut Breakdowns,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> Result<()> {
+        let reader = BufReader::new(File::open(path)?);
+
+        for line in reader.lines().map_while(|l| l.ok()) {
+            let Ok(event) = serde_json::from_str::<AnalyticsEvent>(&line) else {
+                continue;
+            };
+            if event.event.as_deref() != Some("message_send") {
+                continue;
+            }
+
+            let props = &event.properties;
+            let model = props.main_model.clone().unwrap_or_else(|| "unknown".to_string());
+            let mut usage = UsageData::new();
+            usage.input_tokens = props.prompt_tokens.unwrap_or(0);
+            usage.output_tokens = props.completion_tokens.unwrap_or(0);
+            usage.request_count = 1;
+            usage.estimated_cost = props
+                .cost
+                .unwrap_or_else(|| calculate_cost_with_cache(usage.input_tokens, usage.output_tokens, 0, 0, Some(&model)));
+
+            let timestamp = event.time.and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+            stats.add_at(&usage, timestamp, ranges);
+            breakdowns.by_model.entry(model).or_default().add_at(&usage, timestamp, ranges);
+        }
+
+        Ok(())
+    }
+
+    /// "2.3k" / "1,234" / "1.2M" as printed by Aider
+    fn parse_count(s: &str) -> u64 {
+        let s = s.replace(',', "");
+        let (number, scale) = match s.chars().last() {
+            Some('k' | 'K') => (&s[..s.len() - 1], 1_000.0),
+            Some('m' | 'M') => (&s[..s.len() - 1], 1_000_000.0),
+            _ => (s.as_str(), 1.0),
+        };
+        number.parse::<f64>().map(|n| (n * scale).round() as u64).unwrap_or(0)
+    }
+
+    /// Calls `f` with each reply's usage, time and model
+    fn walk_history_file(
+        path: &Path,
+        f: &mut ReplySink,
+    ) -> Result<()> {
+        let file = File::open(path)?;
+        let modified = file.metadata().and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from);
+        let reader = BufReader::new(file);
+
+        let mut session_start: Option<DateTime<Utc>> = None;
+        let mut model: Option<String> = None;
+        // Replies of the current session, dated once it is known whether a later session follows
+        let mut replies: Vec<(UsageData, Option<String>)> = Vec::new();
+
+        for line in reader.lines().map_while(|l| l.ok()) {
+            if let Some(caps) = SESSION_HEADER.captures(&line) {
+                for (usage, model) in replies.drain(..) {
+                    f(usage, session_start, model.as_deref());
+                }
+                session_start = NaiveDateTime::parse_from_str(&caps[1], "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .and_then(|dt| Local.from_local_datetime(&dt).earliest())
+                    .map(|dt| dt.with_timezone(&Utc));
+                model = None;
+                continue;
+            }
+            if let Some(caps) = MODEL_LINE.captures(&line) {
+                model = Some(caps[1].to_string());
+                continue;
+            }
+            let Some(caps) = TOKENS_LINE.captures(&line) else {
+                continue;
+            };
+
+            let mut usage = UsageData::new();
+            usage.input_tokens = Self::parse_count(&caps[1]);
+            usage.cache_write_tokens = caps.get(2).map_or(0, |m| Self::parse_count(m.as_str()));
+            usage.cache_read_tokens = caps.get(3).map_or(0, |m| Self::parse_count(m.as_str()));
+            usage.output_tokens = Self::parse_count(&caps[4]);
+            usage.request_count = 1;
+            usage.estimated_cost = caps
+                .get(5)
+                .and_then(|m| m.as_str().replace(',', "").parse().ok())
+                .unwrap_or_else(|| {
+                    calculate_cost_with_cache(
+                        usage.input_tokens,
+                        usage.output_tokens,
+                        usage.cache_read_tokens,
+                        usage.cache_write_tokens,
+                        model.as_deref(),
+                    )
+                });
+
+            replies.push((usage, model.clone()));
+        }
+
+        // The last session ran until the file was last written
+        let last_write = match (modified, session_start) {
+            (Some(modified), Some(start)) => Some(modified.max(start)),
+            (modified, start) => modified.or(start),
+        };
+        for (usage, model) in replies {
+            f(usage, last_write, model.as_deref());
+        }
+
+        Ok(())
+    }
+
+    /// Reads each chat history once, adding its replies to the stats (when given) and to `records`
+    fn walk_histories(
+        &self,
+        history_files: &[PathBuf],
+        mut stats: Option<(&mut UsageStats, &mut Breakdowns)>,
+        mut records: Option<&mut Vec<UsageRecord>>,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) {
+        for file in history_files {
+            let repo = file.parent().map(Path::to_path_buf);
+            let repo_label = repo
+                .as_ref()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let _ = Self::walk_history_file(file, &mut |usage, timestamp, model| {
+                if let Some((stats, breakdowns)) = stats.as_mut() {
+                    stats.add_at(&usage, timestamp, ranges);
+                    let model = model.unwrap_or("unknown").to_string();
+                    breakdowns.by_model.entry(model).or_default().add_at(&usage, timestamp, ranges);
+                    breakdowns.by_repo.entry(repo_label.clone()).or_default().add_at(&usage, timestamp, ranges);
+                }
+                // Chat histories sit in the repository they belong to
+                if let (Some(records), Some(timestamp)) = (records.as_deref_mut(), timestamp) {
+                    records.push(UsageRecord {
+                        provider: self.name(),
+                        timestamp,
+                        cwd: repo.clone(),
+                        git_branch: None,
+                        model: model.map(str::to_string),
+                        usage,
+                    });
+                }
+            });
+        }
+    }
+
+    /// Usage from the analytics log, else the chat histories; pushes records to `records` when given.
+    /// Records always come from the chat histories, since the analytics log doesn't record a directory.
+    fn scan(&self, records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        let history_files: Vec<PathBuf> = Self::history_files().into_iter().filter(|p| p.exists()).collect();
+
+        // The analytics log covers every repository, so chat histories would double count
+        if let Some(log) = Self::analytics_log().filter(|p| p.exists()) {
+            if records.is_some() {
+                self.walk_histories(&history_files, None, records, &ranges);
+            }
+            if let Err(e) = Self::process_analytics_log(&log, &mut stats, &mut breakdowns, &ranges) {
+                return ProviderResult::error(
+                    self.name(),
+                    self.display_name(),
+                    &format!("Failed to read {}: {}", log.display(), e),
+                );
+            }
+            return ProviderResult::active(
+                self.name(),
+                self.display_name(),
+                stats,
+                &log.to_string_lossy(),
+            )
+            .with_breakdown(UsageBreakdown::from_slices("model", breakdowns.by_model));
+        }
+
+        if history_files.is_empty() {
+            return ProviderResult::not_found(self.name(), self.display_name());
+        }
+        self.walk_histories(&history_files, Some((&mut stats, &mut breakdowns)), records, &ranges);
+
+        let mut breakdown = UsageBreakdown::from_slices("model", breakdowns.by_model);
+        breakdown.extend(UsageBreakdown::from_slices("repository", breakdowns.by_repo));
+
+        ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &format!("{} chat histories", history_files.len()),
+        )
+        .with_breakdown(breakdown)
+        .with_note(format!(
+            "No analytics log found; read from .aider.chat.history.md (set analytics-log in ~/.aider.conf.yml or {} for complete data)",
+            aider::ANALYTICS_LOG_ENV
+        ))
+    }
+}
+
+#[async_trait]
+impl Provider for AiderProvider {
+    fn name(&self) -> &'static str {
+        "aider"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Aider"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::analytics_log().is_some_and(|p| p.exists()) || Self::history_files().iter().any(|p| p.exists())
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        let mut paths: Vec<PathBuf> = aider::config_file().into_iter().collect();
+        match Self::analytics_log() {
+            Some(log) => paths.push(log),
+            None => paths.push(PathBuf::from(format!("{} environment variable", aider::ANALYTICS_LOG_ENV))),
+        }
+        paths.extend(Self::history_files());
+        paths.iter().map(|p| p.to_string_lossy().to_string()).collect()
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        Ok(self.scan(None))
+    }
+
+    fn has_usage_records(&self) -> bool {
+        true
+    }
+
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
+
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        Ok((result, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn parses_counts_as_printed() {
+        assert_eq!(AiderProvider::parse_count("512"), 512);
+        assert_eq!(AiderProvider::parse_count("1,234"), 1234);
+        assert_eq!(AiderProvider::parse_count("2.3k"), 2300);
+        assert_eq!(AiderProvider::parse_count("1.2M"), 1_200_000);
+        assert_eq!(AiderProvider::parse_count("?"), 0);
+    }
+
+    #[test]
+    fn matches_the_history_lines() {
+        let caps = TOKENS_LINE
+            .captures("> Tokens: 2.3k sent, 1.1k cache write, 4.0k cache hit, 512 received. Cost: $0.01 message, $0.05 session.")
+            .unwrap();
+        let groups: Vec<Option<&str>> = (1..=5).map(|i| caps.get(i).map(|m| m.as_str())).collect();
+        assert_eq!(groups, [Some("2.3k"), Some("1.1k"), Some("4.0k"), Some("512"), Some("0.01")]);
+
+        let caps = TOKENS_LINE.captures("> Tokens: 1,024 sent, 88 received.").unwrap();
+        assert_eq!((&caps[1], &caps[4]), ("1,024", "88"));
+        assert!(caps.get(2).is_none() && caps.get(5).is_none());
+
+        assert_eq!(&MODEL_LINE.captures("> Model: gpt-4o with diff edit format").unwrap()[1], "gpt-4o");
+        assert_eq!(&MODEL_LINE.captures("> Main model: claude-3-5-sonnet-20241022 with diff edit format, infinite output").unwrap()[1], "claude-3-5-sonnet-20241022");
+        assert_eq!(&SESSION_HEADER.captures("# aider chat started at 2025-01-31 14:05:12").unwrap()[1], "2025-01-31 14:05:12");
+        assert!(TOKENS_LINE.captures("Tokens: 5 sent, 5 received.").is_none());
+    }
+
+    #[test]
+    fn dates_the_last_session_by_the_last_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(aider::HISTORY_FILE);
+        fs::write(
+            &path,
+            "# aider chat started at 2025-01-30 09:00:00\n\
+             > Model: gpt-4o with diff edit format\n\
+             > Tokens: 1k sent, 100 received. Cost: $0.02 message, $0.02 session.\n\
+             \n\
+             # aider chat started at 2025-01-31 23:00:00\n\
+             > Main model: claude-3-5-sonnet-20241022 with diff edit format\n\
+             > Tokens: 2k sent, 200 received.\n",
+        )
+        .unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_738_500_000);
+        File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+        let mut replies = Vec::new();
+        AiderProvider::walk_history_file(&path, &mut |usage, timestamp, model| {
+            replies.push((usage, timestamp, model.map(str::to_string)));
+        })
+        .unwrap();
+
+        assert_eq!(replies.len(), 2);
+        let first_session = Local.with_ymd_and_hms(2025, 1, 30, 9, 0, 0).unwrap().with_timezone(&Utc);
+        assert_eq!(replies[0].1, Some(first_session));
+        assert_eq!(replies[0].2.as_deref(), Some("gpt-4o"));
+        assert_eq!(replies[0].0.estimated_cost, 0.02);
+        assert_eq!(replies[1].1, Some(DateTime::<Utc>::from(modified)));
+        assert_eq!((replies[1].0.input_tokens, replies[1].0.output_tokens), (2000, 200));
+        assert!(replies[1].0.estimated_cost > 0.0);
+    }
+}
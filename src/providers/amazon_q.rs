@@ -3,14 +3,15 @@
 
 use super::Provider;
 use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::amazon_q;
+use crate::utils::config;
+use crate::utils::paths::{self, amazon_q};
 use crate::utils::time::get_local_time_ranges;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct AmazonQLogEntry {
@@ -31,11 +32,16 @@ impl AmazonQProvider {
         get_local_time_ranges()
     }
 
+    /// Developer log files, including any configured extras
+    fn logs_files() -> Vec<PathBuf> {
+        config::get().data_paths("amazon-q", amazon_q::logs_file())
+    }
+
     fn process_log_file(path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
         let file_mtime = fs::metadata(path)
             .and_then(|m| m.modified())
             .ok()
-            .map(|t| DateTime::<Utc>::from(t));
+            .map(DateTime::<Utc>::from);
 
         if let Ok(content) = fs::read_to_string(path) {
             for line in content.lines() {
@@ -176,36 +182,35 @@ impl Provider for AmazonQProvider {
     }
 
     async fn is_available(&self) -> bool {
-        amazon_q::logs_file().map(|p| p.exists()).unwrap_or(false)
+        Self::logs_files().iter().any(|p| p.exists())
             || amazon_q::config_file().map(|p| p.exists()).unwrap_or(false)
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            amazon_q::logs_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            amazon_q::config_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        let mut paths: Vec<String> = Self::logs_files()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.push(amazon_q::config_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let logs_path = amazon_q::logs_file();
+        let logs_paths: Vec<PathBuf> = Self::logs_files().into_iter().filter(|p| p.exists()).collect();
         let config_path = amazon_q::config_file();
 
-        let has_logs = logs_path.as_ref().map(|p| p.exists()).unwrap_or(false);
         let has_config = config_path.as_ref().map(|p| p.exists()).unwrap_or(false);
 
-        if !has_logs && !has_config {
+        if logs_paths.is_empty() && !has_config {
             return Ok(ProviderResult::not_found(self.name(), self.display_name()));
         }
 
         let ranges = Self::get_time_ranges();
         let mut stats = UsageStats::default();
 
-        // Process local log file
-        if let Some(ref path) = logs_path {
-            if path.exists() {
-                Self::process_log_file(path, &mut stats, &ranges);
-            }
+        // Process local log files
+        for path in &logs_paths {
+            Self::process_log_file(path, &mut stats, &ranges);
         }
 
         // If we found no data but AWS config exists, mark as active but no data
@@ -218,9 +223,11 @@ impl Provider for AmazonQProvider {
             ));
         }
 
-        let data_source = logs_path
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Amazon Q".to_string());
+        let data_source = if logs_paths.is_empty() {
+            "Amazon Q".to_string()
+        } else {
+            paths::join_paths(&logs_paths)
+        };
 
         Ok(ProviderResult::active(
             self.name(),
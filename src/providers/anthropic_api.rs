@@ -0,0 +1,456 @@
+//! Anthropic API Provider
+//! Uses the Anthropic Admin API usage and cost reports (`/v1/organizations/usage_report/messages`,
+//! `/v1/organizations/cost_report`) to fetch organization-wide API usage. Requires an admin key.
+
+use super::{ClaudeCodeProvider, Provider};
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::format::{format_cost, format_number};
+use crate::utils::http::{self, Bucket, FetchError};
+use crate::utils::time::{get_local_time_ranges, ranges_start};
+use crate::utils::tokenizer::calculate_cost_with_cache;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::SecondsFormat;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+struct MessagesResult {
+    uncached_input_tokens: Option<u64>,
+    cache_read_input_tokens: Option<u64>,
+    cache_creation: Option<CacheCreation>,
+    output_tokens: Option<u64>,
+    model: Option<String>,
+    workspace_id: Option<String>,
+    api_key_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CacheCreation {
+    ephemeral_5m_input_tokens: Option<u64>,
+    ephemeral_1h_input_tokens: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CostResult {
+    /// Decimal string in the lowest currency unit (cents)
+    amount: Option<String>,
+    workspace_id: Option<String>,
+    model: Option<String>,
+    /// "tokens" for model usage; other products such as "web_search" and "code_execution"
+    cost_type: Option<String>,
+}
+
+impl CostResult {
+    /// Whether the line bills model tokens, the only spend the usage report counts
+    fn is_tokens(&self) -> bool {
+        match self.cost_type.as_deref() {
+            Some(cost_type) => cost_type == "tokens",
+            None => self.model.is_some(),
+        }
+    }
+}
+
+/// Anthropic API base URL (overridable with `providers.anthropic-api.base_url`)
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com";
+
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Admin keys are distinguishable from regular API keys by prefix
+const ADMIN_KEY_PREFIX: &str = "sk-ant-admin";
+
+/// Largest page the API allows for daily buckets
+const PAGE_LIMIT: &str = "31";
+
+/// Label for usage outside any workspace
+const DEFAULT_WORKSPACE: &str = "default";
+
+pub struct AnthropicApiProvider;
+
+/// Usage grouped the way it is reported
+#[derive(Default)]
+struct Report {
+    stats: UsageStats,
+    by_model: HashMap<String, UsageStats>,
+    by_workspace: HashMap<String, UsageStats>,
+    by_api_key: HashMap<String, UsageStats>,
+    /// Whether costs come from the cost report rather than list prices
+    billed_costs: bool,
+    /// Billed costs other than tokens (web search, code execution), left out of the totals
+    other_costs: f64,
+}
+
+impl AnthropicApiProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Admin key from the environment. `ANTHROPIC_API_KEY` is only used if it holds an admin key.
+    fn get_admin_key() -> Option<String> {
+        let var = |name| std::env::var(name).ok().filter(|k: &String| !k.is_empty());
+        var("A2Z_ANTHROPIC_ADMIN_KEY")
+            .or_else(|| var("ANTHROPIC_ADMIN_KEY"))
+            .or_else(|| var("ANTHROPIC_API_KEY").filter(|k| k.starts_with(ADMIN_KEY_PREFIX)))
+    }
+
+    /// Admin key detection status, for `doctor`
+    pub fn admin_key_status() -> &'static str {
+        match Self::get_admin_key() {
+            Some(key) if key.starts_with(ADMIN_KEY_PREFIX) => "admin key detected",
+            Some(_) => "set, but not an admin key (expected sk-ant-admin...)",
+            None => "not set",
+        }
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Fetch every bucket of a paginated report
+    async fn fetch_buckets<T: DeserializeOwned>(
+        base_url: &str,
+        admin_key: &str,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> std::result::Result<Vec<Bucket<T>>, FetchError> {
+        let client = http::client();
+        http::fetch_buckets(|| {
+            client
+                .get(format!("{}{}", base_url, path))
+                .query(query)
+                .header("x-api-key", admin_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+        })
+        .await
+    }
+
+    async fn fetch_usage_data(
+        base_url: &str,
+        admin_key: &str,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> std::result::Result<Report, FetchError> {
+        let starting_at = ranges_start(ranges).to_rfc3339_opts(SecondsFormat::Secs, true);
+        let query = |group_by: &[&str]| -> Vec<(&str, String)> {
+            let mut query = vec![
+                ("starting_at", starting_at.clone()),
+                ("bucket_width", "1d".to_string()),
+                ("limit", PAGE_LIMIT.to_string()),
+            ];
+            query.extend(group_by.iter().map(|g| ("group_by[]", g.to_string())));
+            query
+        };
+
+        let usage_buckets = Self::fetch_buckets::<MessagesResult>(
+            base_url,
+            admin_key,
+            "/v1/organizations/usage_report/messages",
+            &query(&["model", "workspace_id", "api_key_id"]),
+        )
+        .await?;
+
+        let mut report = Report::default();
+
+        for bucket in &usage_buckets {
+            let timestamp = Some(bucket.time());
+            for result in &bucket.results {
+                let cache_creation = result.cache_creation.as_ref();
+                let mut usage = UsageData::new();
+                usage.input_tokens = result.uncached_input_tokens.unwrap_or(0);
+                usage.output_tokens = result.output_tokens.unwrap_or(0);
+                usage.cache_read_tokens = result.cache_read_input_tokens.unwrap_or(0);
+                usage.cache_write_tokens = cache_creation.and_then(|c| c.ephemeral_5m_input_tokens).unwrap_or(0)
+                    + cache_creation.and_then(|c| c.ephemeral_1h_input_tokens).unwrap_or(0);
+                // List-price estimate; replaced by billed costs below where the report provides them
+                usage.estimated_cost = calculate_cost_with_cache(
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    usage.cache_read_tokens,
+                    usage.cache_write_tokens,
+                    result.model.as_deref(),
+                );
+
+                report.stats.add_at(&usage, timestamp, ranges);
+                for (slices, key, default) in [
+                    (&mut report.by_model, &result.model, "unknown"),
+                    (&mut report.by_workspace, &result.workspace_id, DEFAULT_WORKSPACE),
+                    (&mut report.by_api_key, &result.api_key_id, "unknown"),
+                ] {
+                    let label = key.clone().unwrap_or_else(|| default.to_string());
+                    slices.entry(label).or_default().add_at(&usage, timestamp, ranges);
+                }
+            }
+        }
+
+        // The cost report has no API key dimension, so those slices keep list-price estimates
+        if let Ok(cost_buckets) = Self::fetch_buckets::<CostResult>(
+            base_url,
+            admin_key,
+            "/v1/organizations/cost_report",
+            &query(&["workspace_id", "description"]),
+        )
+        .await
+        {
+            let mut costs = UsageStats::default();
+            let mut model_costs: HashMap<String, UsageStats> = HashMap::new();
+            let mut workspace_costs: HashMap<String, UsageStats> = HashMap::new();
+            let mut matched = false;
+
+            for bucket in &cost_buckets {
+                let timestamp = Some(bucket.time());
+                for result in &bucket.results {
+                    let cents: f64 = result.amount.as_deref().and_then(|a| a.parse().ok()).unwrap_or(0.0);
+                    if !result.is_tokens() {
+                        report.other_costs += cents / 100.0;
+                        continue;
+                    }
+                    matched = true;
+
+                    let mut usage = UsageData::new();
+                    usage.estimated_cost = cents / 100.0;
+
+                    costs.add_at(&usage, timestamp, ranges);
+                    if let Some(ref model) = result.model {
+                        model_costs.entry(model.clone()).or_default().add_at(&usage, timestamp, ranges);
+                    }
+                    let workspace = result.workspace_id.clone().unwrap_or_else(|| DEFAULT_WORKSPACE.to_string());
+                    workspace_costs.entry(workspace).or_default().add_at(&usage, timestamp, ranges);
+                }
+            }
+
+            // Without any token lines the estimates are safer than zero
+            if matched || report.stats.total.total_tokens() == 0 {
+                report.stats.replace_costs(&costs);
+                for (model, stats) in report.by_model.iter_mut() {
+                    stats.replace_costs(&model_costs.remove(model).unwrap_or_default());
+                }
+                for (workspace, stats) in report.by_workspace.iter_mut() {
+                    stats.replace_costs(&workspace_costs.remove(workspace).unwrap_or_default());
+                }
+                report.billed_costs = true;
+            }
+        }
+
+        Ok(report)
+    }
+
+    /// Compare this month's organization usage with the local Claude Code logs.
+    /// Local usage on a Claude subscription is not billed through the API, so it can exceed the API figures.
+    async fn reconcile_with_claude_code(api: &UsageData) -> Option<String> {
+        let local = ClaudeCodeProvider::new().shared_usage().await.usage?.this_month;
+        // Input + output, as in the table
+        let tokens = |u: &UsageData| u.input_tokens + u.output_tokens;
+        if tokens(&local) == 0 {
+            return None;
+        }
+
+        let share = if tokens(api) > 0 {
+            format!(" ({:.0}% of API tokens)", tokens(&local) as f64 / tokens(api) as f64 * 100.0)
+        } else {
+            String::new()
+        };
+        Some(format!(
+            "This month: API reports {} tokens / {}; local Claude Code logs account for {} tokens / ~{}{}",
+            format_number(tokens(api)),
+            format_cost(api.estimated_cost),
+            format_number(tokens(&local)),
+            format_cost(local.estimated_cost),
+            share
+        ))
+    }
+}
+
+#[async_trait]
+impl Provider for AnthropicApiProvider {
+    fn name(&self) -> &'static str {
+        "anthropic-api"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Anthropic API"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::get_admin_key().is_some()
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        vec!["ANTHROPIC_ADMIN_KEY environment variable".to_string()]
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::Api
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let admin_key = match Self::get_admin_key() {
+            Some(k) => k,
+            None => return Ok(ProviderResult::no_key(self.name(), self.display_name())),
+        };
+        if !admin_key.starts_with(ADMIN_KEY_PREFIX) {
+            return Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "ANTHROPIC_ADMIN_KEY is not an admin key (expected sk-ant-admin...). Create one in the Claude Console.",
+            ));
+        }
+
+        let ranges = Self::get_time_ranges();
+        let base_url = config::get().base_url(self.name(), DEFAULT_BASE_URL);
+
+        match Self::fetch_usage_data(&base_url, &admin_key, &ranges).await {
+            Ok(report) => {
+                let mut cost_note = if report.billed_costs {
+                    "Costs are billed token amounts; API key slices use list-price estimates".to_string()
+                } else {
+                    "Cost report unavailable; costs are list-price estimates".to_string()
+                };
+                if report.billed_costs && report.other_costs > 0.0 {
+                    cost_note.push_str(&format!(
+                        "; {} billed for web search, code execution and other tools is not included",
+                        format_cost(report.other_costs)
+                    ));
+                }
+                let reconciliation = Self::reconcile_with_claude_code(&report.stats.this_month).await;
+
+                let mut breakdown = UsageBreakdown::from_slices("model", report.by_model);
+                breakdown.extend(UsageBreakdown::from_slices("workspace", report.by_workspace));
+                breakdown.extend(UsageBreakdown::from_slices("api_key", report.by_api_key));
+
+                let mut result = ProviderResult::active(
+                    self.name(),
+                    self.display_name(),
+                    report.stats,
+                    "Anthropic Admin API",
+                )
+                .with_breakdown(breakdown)
+                .with_note(cost_note);
+                if let Some(note) = reconciliation {
+                    result = result.with_note(note);
+                }
+                Ok(result)
+            }
+            Err(FetchError::Forbidden) => Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "Admin key lacks permission to read usage reports.",
+            )),
+            Err(FetchError::Unauthorized) => Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "Invalid admin key. Please check your ANTHROPIC_ADMIN_KEY.",
+            )),
+            Err(FetchError::NotFound) => Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "Usage report endpoint not found. Admin API access requires an organization account.",
+            )),
+            Err(FetchError::RateLimited) => Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "Rate limited by Anthropic API. Please try again later.",
+            )),
+            Err(FetchError::Network) => Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "Network error connecting to Anthropic API.",
+            )),
+            Err(FetchError::Parse) => Ok(ProviderResult::error(
+                self.name(),
+                self.display_name(),
+                "Failed to parse Anthropic API response.",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::http::test_server;
+    use chrono::{Duration, Utc};
+
+    fn day() -> (String, String) {
+        let start = Utc::now().date_naive().and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let format = |t: chrono::DateTime<Utc>| t.to_rfc3339_opts(SecondsFormat::Secs, true);
+        (format(start), format(start + Duration::days(1)))
+    }
+
+    fn usage_page(model: &str, workspace: Option<&str>, input: u64, next_page: Option<&str>) -> String {
+        let (starting_at, ending_at) = day();
+        serde_json::json!({
+            "data": [{ "starting_at": starting_at, "ending_at": ending_at, "results": [{
+                "uncached_input_tokens": input, "cache_read_input_tokens": 0,
+                "cache_creation": { "ephemeral_5m_input_tokens": 0, "ephemeral_1h_input_tokens": 0 },
+                "output_tokens": 100, "model": model, "workspace_id": workspace, "api_key_id": "apikey_1",
+            }]}],
+            "has_more": next_page.is_some(),
+            "next_page": next_page,
+        })
+        .to_string()
+    }
+
+    fn cost_page(lines: &[(&str, Option<&str>, Option<&str>)]) -> String {
+        let (starting_at, ending_at) = day();
+        let results: Vec<_> = lines
+            .iter()
+            .map(|(cents, model, cost_type)| {
+                serde_json::json!({ "currency": "USD", "amount": cents, "model": model, "cost_type": cost_type, "workspace_id": null })
+            })
+            .collect();
+        serde_json::json!({ "data": [{ "starting_at": starting_at, "ending_at": ending_at, "results": results }], "has_more": false })
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn follows_usage_pages_and_applies_token_costs_only() {
+        let server = test_server::serve(vec![
+            ("page=page_2", usage_page("claude-opus-4-20250514", Some("wrkspc_1"), 2000, None)),
+            ("/usage_report/messages", usage_page("claude-sonnet-4-20250514", None, 1000, Some("page_2"))),
+            (
+                "/cost_report",
+                cost_page(&[
+                    ("150", Some("claude-sonnet-4-20250514"), Some("tokens")),
+                    ("50.5", Some("claude-opus-4-20250514"), Some("tokens")),
+                    ("1000", None, Some("web_search")),
+                    ("20", None, Some("code_execution")),
+                ]),
+            ),
+        ]);
+
+        let report = AnthropicApiProvider::fetch_usage_data(&server.url, "sk-ant-admin-x", &get_local_time_ranges())
+            .await
+            .unwrap();
+
+        assert_eq!(report.stats.total.input_tokens, 3000);
+        assert!(report.billed_costs);
+        assert!((report.stats.total.estimated_cost - 2.005).abs() < 1e-9);
+        assert!((report.stats.today.estimated_cost - 2.005).abs() < 1e-9);
+        assert!((report.other_costs - 10.2).abs() < 1e-9);
+        // Model slices add up to the total
+        let model_costs: f64 = report.by_model.values().map(|s| s.total.estimated_cost).sum();
+        assert!((model_costs - report.stats.total.estimated_cost).abs() < 1e-9);
+        assert_eq!(report.by_workspace["wrkspc_1"].total.input_tokens, 2000);
+        assert_eq!(report.by_workspace[DEFAULT_WORKSPACE].total.input_tokens, 1000);
+        assert_eq!(server.requests().iter().filter(|r| r.contains("/usage_report/messages")).count(), 2);
+    }
+
+    #[tokio::test]
+    async fn cost_report_without_token_lines_keeps_the_estimates() {
+        let server = test_server::serve(vec![
+            ("/usage_report/messages", usage_page("claude-sonnet-4-20250514", None, 1000, None)),
+            ("/cost_report", cost_page(&[("1000", None, Some("web_search"))])),
+        ]);
+
+        let report = AnthropicApiProvider::fetch_usage_data(&server.url, "sk-ant-admin-x", &get_local_time_ranges())
+            .await
+            .unwrap();
+
+        assert!(!report.billed_costs);
+        assert_eq!(
+            report.stats.total.estimated_cost,
+            calculate_cost_with_cache(1000, 100, 0, 0, Some("claude-sonnet-4-20250514"))
+        );
+    }
+}
@@ -1,18 +1,27 @@
 //! Claude Code Provider (CLI + IDE Extension)
-//! Reads usage data from ~/.claude/projects/ directory
+//! Reads usage data from ~/.claude/projects/, ~/.config/claude/projects/ and any CLAUDE_CONFIG_DIR roots
 //! Note: Both Claude Code CLI and VS Code/Cursor extensions share this data store
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::claude_code;
+use crate::types::{ProviderResult, TimeRange, ToolAnalytics, ToolCount, UsageBreakdown, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, claude_code};
 use crate::utils::time::get_local_time_ranges;
 use crate::utils::tokenizer::calculate_cost_with_cache;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use tokio::sync::OnceCell;
+
+/// Model assumed for pricing when a message doesn't name one
+const DEFAULT_MODEL: &str = "claude-sonnet-4";
+
+/// This run's scan of the real home, shared with the Anthropic API provider's reconciliation
+static REAL_HOME_SCAN: OnceCell<ProviderResult> = OnceCell::const_new();
 
 #[derive(Debug, Deserialize)]
 struct ClaudeMessage {
@@ -20,15 +29,29 @@ struct ClaudeMessage {
     #[allow(dead_code)]
     msg_type: Option<String>,
     message: Option<MessageContent>,
+    #[serde(rename = "requestId")]
+    request_id: Option<String>,
     #[serde(rename = "costUSD")]
     cost_usd: Option<f64>,
     timestamp: Option<String>,
+    /// Where the request came from: "cli", "claude-vscode", "sdk-ts", "sdk-py", ...
+    entrypoint: Option<String>,
+    /// Working directory of the session
+    cwd: Option<String>,
+    /// Git branch checked out in `cwd` when the message was written
+    #[serde(rename = "gitBranch")]
+    git_branch: Option<String>,
+    /// True for subagent (Task tool) traffic running off the main thread
+    #[serde(rename = "isSidechain")]
+    is_sidechain: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
 struct MessageContent {
+    id: Option<String>,
     usage: Option<UsageInfo>,
-    #[allow(dead_code)]
+    /// String for plain user prompts, array of blocks (text, tool_use, ...) otherwise
+    content: Option<serde_json::Value>,
     model: Option<String>,
 }
 
@@ -40,6 +63,25 @@ struct UsageInfo {
     cache_creation_input_tokens: Option<u64>,
 }
 
+/// State carried across every JSONL file in one scan
+#[derive(Default)]
+struct ScanState {
+    /// `message.id:requestId` keys already counted
+    seen: HashSet<String>,
+    /// Entries skipped because they were already counted from another file or line
+    duplicates: u64,
+}
+
+/// Callback for `walk_messages`: message, its usage (first occurrence only), timestamp
+type MessageVisitor<'a> = dyn FnMut(&ClaudeMessage, Option<&UsageData>, DateTime<Utc>) + 'a;
+
+/// Per-slice usage collected alongside the provider totals
+#[derive(Default)]
+struct Breakdowns {
+    by_entrypoint: HashMap<String, UsageStats>,
+    by_mode: HashMap<String, UsageStats>,
+}
+
 pub struct ClaudeCodeProvider;
 
 impl ClaudeCodeProvider {
@@ -80,9 +122,14 @@ impl ClaudeCodeProvider {
             }
         }
 
-        if let Some(cost) = msg.cost_usd {
-            usage.estimated_cost = cost;
-        }
+        // Older transcripts record `costUSD`; otherwise price the message with its own model
+        usage.estimated_cost = msg.cost_usd.unwrap_or_else(|| {
+            calculate_cost_with_cache(
+                usage.input_tokens, usage.output_tokens,
+                usage.cache_read_tokens, usage.cache_write_tokens,
+                Some(Self::message_model(msg).unwrap_or(DEFAULT_MODEL))
+            )
+        });
 
         // Count as a request if there are any tokens (input, output, or cache)
         if usage.input_tokens > 0 || usage.output_tokens > 0 || usage.cache_read_tokens > 0 || usage.cache_write_tokens > 0 {
@@ -92,19 +139,211 @@ impl ClaudeCodeProvider {
         usage
     }
 
+    /// Resumed and forked sessions copy earlier assistant messages into the new JSONL,
+    /// and streaming writes one line per content block; all share `message.id` + `requestId`.
+    fn dedup_key(msg: &ClaudeMessage) -> Option<String> {
+        let message_id = msg.message.as_ref()?.id.as_ref()?;
+        let request_id = msg.request_id.as_ref()?;
+        Some(format!("{}:{}", message_id, request_id))
+    }
+
     fn parse_timestamp(ts: Option<&String>) -> Option<DateTime<Utc>> {
         ts.and_then(|s| DateTime::parse_from_rfc3339(s).ok())
             .map(|dt| dt.with_timezone(&Utc))
     }
 
-    fn process_directory(dir: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
+    /// Entrypoint label for a message; missing values come from older Claude Code versions
+    fn entrypoint_label(msg: &ClaudeMessage) -> String {
+        msg.entrypoint.clone().unwrap_or_else(|| "unknown".to_string())
+    }
+
+    /// SDK entrypoints ("sdk-ts", "sdk-py", "sdk-cli") are programmatic; everything else is interactive
+    fn mode_label(msg: &ClaudeMessage) -> &'static str {
+        match msg.entrypoint.as_deref() {
+            Some(e) if e.starts_with("sdk") => "sdk",
+            _ => "interactive",
+        }
+    }
+
+    fn message_model(msg: &ClaudeMessage) -> Option<&str> {
+        msg.message.as_ref().and_then(|m| m.model.as_deref())
+    }
+
+    /// Candidate `projects` directories across all Claude config roots, plus configured extras
+    fn projects_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("claude-code", claude_code::projects_dirs())
+    }
+
+    /// Existing `projects` directories across all Claude config roots
+    fn existing_projects_dirs() -> Vec<PathBuf> {
+        Self::projects_dirs()
+            .into_iter()
+            .filter(|p| p.exists())
+            .collect()
+    }
+
+    /// `tool_use` blocks in an assistant message as (tool_use id, tool name)
+    fn tool_uses(msg: &ClaudeMessage) -> Vec<(String, String)> {
+        let blocks = match msg.message.as_ref().and_then(|m| m.content.as_ref()) {
+            Some(serde_json::Value::Array(blocks)) => blocks,
+            _ => return Vec::new(),
+        };
+
+        blocks
+            .iter()
+            .filter(|b| b.get("type").and_then(|t| t.as_str()) == Some("tool_use"))
+            .filter_map(|b| {
+                let name = b.get("name")?.as_str()?.to_string();
+                let id = b.get("id").and_then(|i| i.as_str()).unwrap_or_default().to_string();
+                Some((id, name))
+            })
+            .collect()
+    }
+
+    /// MCP tools are named `mcp__<server>__<tool>`
+    fn mcp_server(tool_name: &str) -> Option<&str> {
+        let rest = tool_name.strip_prefix("mcp__")?;
+        rest.split("__").next().filter(|s| !s.is_empty())
+    }
+
+    fn sorted_counts(counts: HashMap<String, u64>) -> Vec<ToolCount> {
+        let mut counts: Vec<ToolCount> = counts
+            .into_iter()
+            .map(|(name, calls)| ToolCount { name, calls })
+            .collect();
+        counts.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| a.name.cmp(&b.name)));
+        counts
+    }
+
+    /// Tool-call counts and main-thread vs subagent usage across all Claude Code roots,
+    /// under the real home and each alternative home root.
+    /// Returns None if no Claude Code data directory exists.
+    pub fn tool_analytics(homes: &[PathBuf]) -> Option<ToolAnalytics> {
+        let mut projects_dirs = Self::existing_projects_dirs();
+        for home in homes {
+            projects_dirs.extend(paths::with_home_sync(home.clone(), Self::existing_projects_dirs));
+        }
+
+        if projects_dirs.is_empty() {
+            return None;
+        }
+
+        let mut state = ScanState::default();
+        let mut seen_tool_ids: HashSet<String> = HashSet::new();
+        let mut tools: HashMap<String, u64> = HashMap::new();
+        let mut mcp_servers: HashMap<String, u64> = HashMap::new();
+        let mut main_thread = UsageStats::default();
+        let mut subagents = UsageStats::default();
+
+        for projects_dir in &projects_dirs {
+            Self::walk_messages(projects_dir, &mut state, &mut |msg, usage, _| {
+                // Streamed and resumed transcripts repeat blocks; tool_use ids are unique per call
+                for (id, name) in Self::tool_uses(msg) {
+                    if !id.is_empty() && !seen_tool_ids.insert(id) {
+                        continue;
+                    }
+                    if let Some(server) = Self::mcp_server(&name) {
+                        *mcp_servers.entry(server.to_string()).or_default() += 1;
+                    }
+                    *tools.entry(name).or_default() += 1;
+                }
+
+                if let Some(usage) = usage {
+                    if msg.is_sidechain.unwrap_or(false) {
+                        subagents.total.add(usage);
+                    } else {
+                        main_thread.total.add(usage);
+                    }
+                }
+            });
+        }
+
+        Some(ToolAnalytics {
+            tools: Self::sorted_counts(tools),
+            mcp_servers: Self::sorted_counts(mcp_servers),
+            main_thread: main_thread.total,
+            subagents: subagents.total,
+            data_source: paths::join_paths(&projects_dirs),
+        })
+    }
+
+    /// Usage under the current home, reusing this run's scan when it is the real home
+    pub async fn shared_usage(&self) -> ProviderResult {
+        if paths::alternate_home().is_some() {
+            return self.scan(None);
+        }
+        REAL_HOME_SCAN.get_or_init(|| async { self.scan(None) }).await.clone()
+    }
+
+    /// Usage across all roots, pushing each counted message to `records` when given
+    fn scan(&self, mut records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let projects_dirs = Self::existing_projects_dirs();
+
+        if projects_dirs.is_empty() {
+            return ProviderResult::not_found(self.name(), self.display_name());
+        }
+
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        // Shared across roots so sessions mirrored into several roots are only counted once
+        let mut state = ScanState::default();
+        let mut breakdowns = Breakdowns::default();
+
+        // Recursively find and process all JSONL files
+        for projects_dir in &projects_dirs {
+            Self::walk_messages(projects_dir, &mut state, &mut |msg, usage, msg_time| {
+                let Some(usage) = usage else { return };
+                if let Some(records) = records.as_deref_mut() {
+                    records.push(UsageRecord {
+                        provider: self.name(),
+                        timestamp: msg_time,
+                        cwd: msg.cwd.as_ref().map(PathBuf::from),
+                        git_branch: msg.git_branch.clone().filter(|b| !b.is_empty()),
+                        model: Self::message_model(msg).map(str::to_string),
+                        usage: usage.clone(),
+                    });
+                }
+
+                let msg_time = Some(msg_time);
+                stats.add_at(usage, msg_time, &ranges);
+                breakdowns.by_entrypoint
+                    .entry(Self::entrypoint_label(msg))
+                    .or_default()
+                    .add_at(usage, msg_time, &ranges);
+                breakdowns.by_mode
+                    .entry(Self::mode_label(msg).to_string())
+                    .or_default()
+                    .add_at(usage, msg_time, &ranges);
+            });
+        }
+
+        let mut breakdown = UsageBreakdown::from_slices("entrypoint", breakdowns.by_entrypoint);
+        breakdown.extend(UsageBreakdown::from_slices("mode", breakdowns.by_mode));
+
+        let mut result = ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &paths::join_paths(&projects_dirs),
+        )
+        .with_breakdown(breakdown);
+        if state.duplicates > 0 {
+            result = result.with_note(format!("{} duplicate messages removed (resumed/forked sessions)", state.duplicates));
+        }
+        result
+    }
+
+    /// Walk every JSONL file under `dir` (including subagents/) and call `on_message` for each line.
+    /// Usage is passed only for the first occurrence of each message that carries tokens,
+    /// so callers can sum it without double counting.
+    fn walk_messages(dir: &Path, state: &mut ScanState, on_message: &mut MessageVisitor) {
         if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
 
                 if path.is_dir() {
                     // Recursively process subdirectories (including subagents/)
-                    Self::process_directory(&path, stats, ranges);
+                    Self::walk_messages(&path, state, on_message);
                 } else if path.extension().map(|e| e == "jsonl").unwrap_or(false) {
                     // Process JSONL file
                     let messages = Self::parse_jsonl_file(&path);
@@ -116,23 +355,21 @@ impl ClaudeCodeProvider {
                     for msg in messages {
                         let usage = Self::extract_usage(&msg);
                         // Include messages with any tokens (input, output, cache read, or cache write)
-                        if usage.input_tokens > 0 || usage.output_tokens > 0 || usage.cache_read_tokens > 0 || usage.cache_write_tokens > 0 {
-                            stats.total.add(&usage);
-
-                            let msg_time = Self::parse_timestamp(msg.timestamp.as_ref())
-                                .or(file_mtime)
-                                .unwrap_or_else(Utc::now);
-
-                            if ranges.0.contains(msg_time) {
-                                stats.today.add(&usage);
-                            }
-                            if ranges.1.contains(msg_time) {
-                                stats.this_week.add(&usage);
-                            }
-                            if ranges.2.contains(msg_time) {
-                                stats.this_month.add(&usage);
+                        let mut counted = usage.input_tokens > 0 || usage.output_tokens > 0 || usage.cache_read_tokens > 0 || usage.cache_write_tokens > 0;
+                        if counted {
+                            if let Some(key) = Self::dedup_key(&msg) {
+                                if !state.seen.insert(key) {
+                                    state.duplicates += 1;
+                                    counted = false;
+                                }
                             }
                         }
+
+                        let msg_time = Self::parse_timestamp(msg.timestamp.as_ref())
+                            .or(file_mtime)
+                            .unwrap_or_else(Utc::now);
+
+                        on_message(&msg, counted.then_some(&usage), msg_time);
                     }
                 }
             }
@@ -151,59 +388,38 @@ impl Provider for ClaudeCodeProvider {
     }
 
     async fn is_available(&self) -> bool {
-        claude_code::projects_dir()
-            .map(|p| p.exists())
-            .unwrap_or(false)
+        Self::projects_dirs().iter().any(|p| p.exists())
     }
 
-    fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            claude_code::projects_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            claude_code::config_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+    fn has_usage_records(&self) -> bool {
+        true
     }
 
-    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let projects_dir = match claude_code::projects_dir() {
-            Some(p) if p.exists() => p,
-            _ => return Ok(ProviderResult::not_found(self.name(), self.display_name())),
-        };
-
-        let (today_range, week_range, month_range) = Self::get_time_ranges();
-        let mut stats = UsageStats::default();
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
 
-        // Recursively find and process all JSONL files
-        Self::process_directory(&projects_dir, &mut stats, &(today_range, week_range, month_range));
-
-        // Calculate costs if not already set (include cache tokens in calculation)
-        if stats.total.estimated_cost == 0.0 && stats.total.total_tokens() > 0 {
-            stats.today.estimated_cost = calculate_cost_with_cache(
-                stats.today.input_tokens, stats.today.output_tokens,
-                stats.today.cache_read_tokens, stats.today.cache_write_tokens,
-                Some("claude-sonnet-4")
-            );
-            stats.this_week.estimated_cost = calculate_cost_with_cache(
-                stats.this_week.input_tokens, stats.this_week.output_tokens,
-                stats.this_week.cache_read_tokens, stats.this_week.cache_write_tokens,
-                Some("claude-sonnet-4")
-            );
-            stats.this_month.estimated_cost = calculate_cost_with_cache(
-                stats.this_month.input_tokens, stats.this_month.output_tokens,
-                stats.this_month.cache_read_tokens, stats.this_month.cache_write_tokens,
-                Some("claude-sonnet-4")
-            );
-            stats.total.estimated_cost = calculate_cost_with_cache(
-                stats.total.input_tokens, stats.total.output_tokens,
-                stats.total.cache_read_tokens, stats.total.cache_write_tokens,
-                Some("claude-sonnet-4")
-            );
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        if paths::alternate_home().is_none() {
+            let _ = REAL_HOME_SCAN.set(result.clone());
         }
+        Ok((result, records))
+    }
 
-        Ok(ProviderResult::active(
-            self.name(),
-            self.display_name(),
-            stats,
-            &projects_dir.to_string_lossy(),
-        ))
+    fn get_paths_to_check(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Self::projects_dirs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.push(claude_code::config_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        Ok(self.shared_usage().await)
     }
 }
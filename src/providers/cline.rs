@@ -1,17 +1,33 @@
-//! Cline Provider (including Roo Code fork)
+//! Cline Provider (including Roo Code, Kilo Code and other forks)
 //! Reads usage data from VS Code extension storage
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::cline;
+use crate::types::{ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, cline, vscode};
 use crate::utils::time::get_local_time_ranges;
-use crate::utils::tokenizer::calculate_cost;
+use crate::utils::format::format_number;
+use crate::utils::tokenizer::calculate_cost_with_cache;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
+use once_cell::sync::Lazy;
+use regex_lite::Regex;
+use serde::de::{Deserializer, SeqAccess, Visitor};
 use serde::Deserialize;
-use std::fs;
-use std::path::Path;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+
+/// Pricing used when a task's model is unknown
+const DEFAULT_MODEL: &str = "claude-sonnet-4";
+
+/// `# Current Working Directory (/home/me/project) Files` in Cline's environment details;
+/// Roo Code and Kilo Code say "Workspace Directory"
+static WORKSPACE_LINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"# Current (?:Working|Workspace) Directory \(([^)]+)\) Files").unwrap());
 
 #[derive(Debug, Deserialize)]
 struct ClineTaskData {
@@ -28,6 +44,83 @@ struct ClineTaskData {
     ts: Option<i64>,
 }
 
+/// One entry of a task's `ui_messages.json`
+#[derive(Debug, Deserialize)]
+struct UiMessage {
+    ts: Option<i64>,
+    say: Option<String>,
+    /// For `api_req_started`, a JSON object with the request's token counts and cost
+    text: Option<String>,
+    /// Model active when the message was added (recent Cline versions)
+    #[serde(rename = "modelInfo")]
+    model_info: Option<HistoryModelInfo>,
+}
+
+impl UiMessage {
+    fn model(&self) -> Option<&str> {
+        self.model_info.as_ref()?.model_id.as_deref().filter(|m| !m.is_empty())
+    }
+}
+
+/// Payload of an `api_req_started` message
+#[derive(Debug, Deserialize)]
+struct ApiRequestInfo {
+    #[serde(rename = "tokensIn")]
+    tokens_in: Option<u64>,
+    #[serde(rename = "tokensOut")]
+    tokens_out: Option<u64>,
+    #[serde(rename = "cacheWrites")]
+    cache_writes: Option<u64>,
+    #[serde(rename = "cacheReads")]
+    cache_reads: Option<u64>,
+    cost: Option<f64>,
+}
+
+/// The model fields of one turn in `api_conversation_history.json`; the content is skipped
+#[derive(Debug, Deserialize)]
+struct HistoryTurn {
+    #[serde(rename = "modelInfo")]
+    model_info: Option<HistoryModelInfo>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HistoryModelInfo {
+    #[serde(rename = "modelId")]
+    model_id: Option<String>,
+}
+
+impl HistoryTurn {
+    fn into_model(self) -> Option<String> {
+        self.model_info
+            .and_then(|info| info.model_id)
+            .or(self.model)
+            .filter(|m| !m.is_empty())
+    }
+}
+
+/// Walks the history array and stops at the first turn that names its model, so long
+/// conversations aren't read in full
+struct FirstModel<'a>(&'a mut Option<String>);
+
+impl<'de> Visitor<'de> for FirstModel<'_> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an array of conversation turns")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        while let Some(turn) = seq.next_element::<HistoryTurn>()? {
+            if let Some(model) = turn.into_model() {
+                *self.0 = Some(model);
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, Deserialize)]
 struct RooUsageTracking {
     #[serde(rename = "totalInputTokens")]
@@ -42,6 +135,33 @@ struct RooUsageTracking {
     total_cost: Option<f64>,
 }
 
+/// Receives the usage, time and model of each request
+type RequestSink<'a> = dyn FnMut(&UsageData, Option<DateTime<Utc>>, Option<&str>) + 'a;
+
+/// Usage accumulated while scanning task directories
+#[derive(Default)]
+struct TaskTotals {
+    stats: UsageStats,
+    by_model: HashMap<String, UsageStats>,
+}
+
+impl TaskTotals {
+    fn add(&mut self, usage: &UsageData, timestamp: Option<DateTime<Utc>>, model: Option<&str>, ranges: &(TimeRange, TimeRange, TimeRange)) {
+        self.stats.add_at(usage, timestamp, ranges);
+        self.by_model
+            .entry(model.unwrap_or("unknown").to_string())
+            .or_default()
+            .add_at(usage, timestamp, ranges);
+    }
+
+    fn merge(&mut self, other: &TaskTotals) {
+        self.stats.merge(&other.stats);
+        for (model, stats) in &other.by_model {
+            self.by_model.entry(model.clone()).or_default().merge(stats);
+        }
+    }
+}
+
 pub struct ClineProvider;
 
 impl ClineProvider {
@@ -53,7 +173,21 @@ impl ClineProvider {
         get_local_time_ranges()
     }
 
-    fn get_roo_usage_tracking() -> Option<(UsageStats, String)> {
+    /// Task directories of every Cline-family extension (built-in and configured) in every
+    /// VS Code family editor, plus configured extra paths
+    fn tasks_dirs() -> Vec<(&'static str, PathBuf)> {
+        let config = config::get();
+        let defaults = cline::FORKS
+            .iter()
+            .map(|(_, id)| *id)
+            .chain(config.extensions("cline").iter().map(String::as_str))
+            .flat_map(cline::tasks_dirs)
+            .collect();
+        config.labeled_data_paths("cline", defaults)
+    }
+
+    /// Roo Code's lifetime totals from `~/.roo/usage-tracking.json` (no timestamps)
+    fn get_roo_usage_tracking() -> Option<(UsageData, PathBuf)> {
         let tracking_path = cline::roo_usage_tracking()?;
 
         if !tracking_path.exists() {
@@ -70,25 +204,38 @@ impl ClineProvider {
         usage.cache_write_tokens = data.total_cache_write_tokens.unwrap_or(0);
         usage.estimated_cost = data.total_cost.unwrap_or(0.0);
 
-        // Roo's tracking file only has totals, not time-based
-        let mut stats = UsageStats::default();
-        stats.total = usage;
-
-        Some((stats, tracking_path.to_string_lossy().to_string()))
+        Some((usage, tracking_path))
     }
 
-    fn process_tasks_dir(tasks_dir: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
-        if let Ok(entries) = fs::read_dir(tasks_dir) {
-            for entry in entries.flatten() {
-                if entry.file_type().map(|t| t.is_dir()).unwrap_or(false) {
-                    let task_path = entry.path();
-                    Self::process_task_dir(&task_path, stats, ranges);
-                }
-            }
+    fn parse_ts(ts: i64) -> Option<DateTime<Utc>> {
+        if ts > 1_000_000_000_000 {
+            Utc.timestamp_millis_opt(ts).single()
+        } else {
+            Utc.timestamp_opt(ts, 0).single()
         }
     }
 
-    fn process_task_dir(task_path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
+    fn task_dirs(tasks_dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(tasks_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+                    .map(|e| e.path())
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Calls `f` with the usage, time and model of each request of a task
+    fn walk_task(task_path: &Path, f: &mut RequestSink) {
+        // Per-request usage from ui_messages.json, when the extension recorded it; the
+        // history's model covers messages that don't name one
+        let model = Self::task_model(task_path);
+        if Self::process_ui_messages(&task_path.join("ui_messages.json"), model.as_deref(), f) {
+            return;
+        }
+
         // Look for task.json
         let task_json = task_path.join("task.json");
         if task_json.exists() {
@@ -99,53 +246,190 @@ impl ClineProvider {
                         .ok()
                         .and_then(|t| DateTime::<Utc>::from(t).into());
 
-                    Self::process_task_data(&data, stats, ranges, file_mtime);
+                    Self::process_task_data(&data, model.as_deref(), file_mtime, f);
                 }
             }
         }
     }
 
+    /// Workspace a task ran in, from the environment details of its first prompt
+    fn task_workspace(task_path: &Path) -> Option<PathBuf> {
+        let content = fs::read_to_string(task_path.join("api_conversation_history.json")).ok()?;
+        let caps = WORKSPACE_LINE.captures(&content)?;
+        // The path sits inside a JSON string, so backslashes are escaped
+        Some(PathBuf::from(caps[1].replace("\\\\", "\\")))
+    }
+
+    /// Model of a task's first named turn in `api_conversation_history.json`, for requests
+    /// whose UI messages don't name one
+    fn task_model(task_path: &Path) -> Option<String> {
+        let file = File::open(task_path.join("api_conversation_history.json")).ok()?;
+        let mut model = None;
+        // Stopping early leaves the array unfinished, which the deserializer reports as an error
+        let _ = serde_json::Deserializer::from_reader(BufReader::new(file)).deserialize_seq(FirstModel(&mut model));
+        model
+    }
+
+    /// Estimated cost of a request whose extension didn't record one
+    fn estimate_cost(usage: &UsageData, model: Option<&str>) -> f64 {
+        calculate_cost_with_cache(
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_tokens,
+            usage.cache_write_tokens,
+            Some(model.unwrap_or(DEFAULT_MODEL)),
+        )
+    }
+
+    /// Add each `api_req_started` entry as one request, priced at the model of the nearest
+    /// message at or before it that names one (else the first after it, else `task_model`).
+    /// Returns false if the file is missing or has no such entries, so the caller can fall
+    /// back to `task.json`.
+    fn process_ui_messages(
+        path: &Path,
+        task_model: Option<&str>,
+        f: &mut RequestSink,
+    ) -> bool {
+        let Ok(content) = fs::read_to_string(path) else {
+            return false;
+        };
+        let Ok(messages) = serde_json::from_str::<Vec<UiMessage>>(&content) else {
+            return false;
+        };
+
+        let mut model = messages.iter().find_map(UiMessage::model).or(task_model);
+
+        let mut found = false;
+        for message in &messages {
+            model = message.model().or(model);
+            if message.say.as_deref() != Some("api_req_started") {
+                continue;
+            }
+            let Some(info) = message.text.as_deref().and_then(|t| serde_json::from_str::<ApiRequestInfo>(t).ok()) else {
+                continue;
+            };
+            found = true;
+
+            let mut usage = UsageData::new();
+            usage.input_tokens = info.tokens_in.unwrap_or(0);
+            usage.output_tokens = info.tokens_out.unwrap_or(0);
+            usage.cache_read_tokens = info.cache_reads.unwrap_or(0);
+            usage.cache_write_tokens = info.cache_writes.unwrap_or(0);
+            usage.request_count = 1;
+            // A reported cost of 0 (free or local models) is kept
+            usage.estimated_cost = info.cost.unwrap_or_else(|| Self::estimate_cost(&usage, model));
+
+            // Requests that were cancelled before the response arrived carry no tokens
+            if usage.total_tokens() > 0 {
+                f(&usage, message.ts.and_then(Self::parse_ts), model);
+            }
+        }
+
+        found
+    }
+
     fn process_task_data(
         data: &ClineTaskData,
-        stats: &mut UsageStats,
-        ranges: &(TimeRange, TimeRange, TimeRange),
+        model: Option<&str>,
         file_mtime: Option<DateTime<Utc>>,
+        f: &mut RequestSink,
     ) {
         let mut usage = UsageData::new();
         usage.input_tokens = data.tokens_in.unwrap_or(0);
         usage.output_tokens = data.tokens_out.unwrap_or(0);
         usage.cache_read_tokens = data.cache_reads.unwrap_or(0);
         usage.cache_write_tokens = data.cache_writes.unwrap_or(0);
-        usage.estimated_cost = data.total_cost.unwrap_or(0.0);
+        usage.estimated_cost = data.total_cost.unwrap_or_else(|| Self::estimate_cost(&usage, model));
         usage.request_count = 1;
 
         if usage.input_tokens > 0 || usage.output_tokens > 0 {
-            stats.total.add(&usage);
-
             // Determine timestamp
-            let timestamp = data.ts
-                .map(|ts| {
-                    if ts > 1_000_000_000_000 {
-                        Utc.timestamp_millis_opt(ts).single()
-                    } else {
-                        Utc.timestamp_opt(ts, 0).single()
+            let timestamp = data.ts.and_then(Self::parse_ts).or(file_mtime);
+            f(&usage, timestamp, model);
+        }
+    }
+
+    /// Usage across all task directories, pushing each request to `records` when given
+    fn scan(&self, mut records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let tasks_dirs: Vec<(&'static str, PathBuf)> = Self::tasks_dirs()
+            .into_iter()
+            .filter(|(_, p)| p.exists())
+            .collect();
+        let roo_tracking = Self::get_roo_usage_tracking();
+
+        if tasks_dirs.is_empty() && roo_tracking.is_none() {
+            return ProviderResult::not_found(self.name(), self.display_name());
+        }
+
+        let ranges = Self::get_time_ranges();
+        let extensions = config::get().extensions("cline");
+        let mut totals = TaskTotals::default();
+        let mut by_fork: HashMap<String, UsageStats> = HashMap::new();
+        let mut by_editor: HashMap<String, UsageStats> = HashMap::new();
+
+        for (editor, tasks_dir) in &tasks_dirs {
+            let mut dir_totals = TaskTotals::default();
+            for task_path in Self::task_dirs(tasks_dir) {
+                let workspace = records.is_some().then(|| Self::task_workspace(&task_path)).flatten();
+                Self::walk_task(&task_path, &mut |usage, timestamp, model| {
+                    dir_totals.add(usage, timestamp, model, &ranges);
+                    if let (Some(records), Some(timestamp)) = (records.as_deref_mut(), timestamp) {
+                        records.push(UsageRecord {
+                            provider: self.name(),
+                            timestamp,
+                            cwd: workspace.clone(),
+                            git_branch: None,
+                            model: model.map(str::to_string),
+                            usage: usage.clone(),
+                        });
                     }
-                })
-                .flatten()
-                .or(file_mtime);
+                });
+            }
+            // Configured paths outside any extension's storage are assumed to be Cline's
+            let fork = cline::fork_name(tasks_dir, extensions).unwrap_or_else(|| "Cline".to_string());
+            by_fork.entry(fork).or_default().merge(&dir_totals.stats);
+            by_editor.entry(editor.to_string()).or_default().merge(&dir_totals.stats);
+            totals.merge(&dir_totals);
+        }
 
-            if let Some(ts) = timestamp {
-                if ranges.0.contains(ts) {
-                    stats.today.add(&usage);
-                }
-                if ranges.1.contains(ts) {
-                    stats.this_week.add(&usage);
-                }
-                if ranges.2.contains(ts) {
-                    stats.this_month.add(&usage);
-                }
+        let TaskTotals { mut stats, by_model } = totals;
+
+        let mut sources: Vec<PathBuf> = tasks_dirs.into_iter().map(|(_, p)| p).collect();
+        let mut notes = Vec::new();
+
+        // Roo's lifetime counters also cover tasks since deleted from history. They carry
+        // no timestamps, so anything beyond the task history only counts towards Total.
+        if let Some((tracking, tracking_path)) = roo_tracking {
+            let roo_tasks = by_fork.get("Roo Code").map(|s| s.total.clone()).unwrap_or_default();
+            // Usage beyond what the task history accounts for (typically tasks deleted from history)
+            let untracked = tracking.saturating_sub(&roo_tasks);
+            if untracked.total_tokens() > 0 {
+                notes.push(format!(
+                    "Roo usage tracking reports {} more tokens than task history (deleted tasks?); added to Total only",
+                    format_number(untracked.total_tokens())
+                ));
+                stats.total.add(&untracked);
+            } else if roo_tasks.total_tokens() > 0 {
+                notes.push("Roo usage tracking matches task history".to_string());
             }
+            sources.push(tracking_path);
+        }
+
+        let mut breakdown = UsageBreakdown::from_slices("fork", by_fork);
+        breakdown.extend(UsageBreakdown::from_slices("model", by_model));
+        breakdown.extend(UsageBreakdown::from_slices("editor", by_editor));
+
+        let mut result = ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &paths::join_paths(&sources),
+        )
+        .with_breakdown(breakdown);
+        for note in notes {
+            result = result.with_note(note);
         }
+        result
     }
 }
 
@@ -160,60 +444,115 @@ impl Provider for ClineProvider {
     }
 
     async fn is_available(&self) -> bool {
-        cline::original_tasks_dir().map(|p| p.exists()).unwrap_or(false)
-            || cline::roo_code_tasks_dir().map(|p| p.exists()).unwrap_or(false)
+        Self::tasks_dirs().iter().any(|(_, p)| p.exists())
             || cline::roo_usage_tracking().map(|p| p.exists()).unwrap_or(false)
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            cline::original_tasks_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            cline::roo_code_tasks_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            cline::roo_usage_tracking().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        let mut paths: Vec<String> = vscode::installed(Self::tasks_dirs())
+            .iter()
+            .map(|(_, p)| p.to_string_lossy().to_string())
+            .collect();
+        paths.push(cline::roo_usage_tracking().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        // First, check for Roo Code's usage-tracking.json (easiest and most accurate)
-        if let Some((stats, data_source)) = Self::get_roo_usage_tracking() {
-            return Ok(ProviderResult::active(
-                self.name(),
-                "Cline (Roo)",
-                stats,
-                &data_source,
-            ));
-        }
+        Ok(self.scan(None))
+    }
 
-        // Fall back to scanning task directories
-        let tasks_dir = if cline::roo_code_tasks_dir().map(|p| p.exists()).unwrap_or(false) {
-            cline::roo_code_tasks_dir()
-        } else {
-            cline::original_tasks_dir()
-        };
+    fn has_usage_records(&self) -> bool {
+        true
+    }
+
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
+
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        Ok((result, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn task_model_is_the_first_named_model() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("api_conversation_history.json"),
+            r#"[
+                {"role":"user","content":[{"type":"text","text":"hi"}]},
+                {"role":"assistant","content":[],"modelInfo":{"modelId":"claude-opus-4","providerId":"anthropic"}},
+                {"role":"assistant","content":[],"modelInfo":{"modelId":"gpt-4o"}},
+                {"role":"user","content":"truncated"#,
+        )
+        .unwrap();
 
-        let tasks_dir = match tasks_dir {
-            Some(p) if p.exists() => p,
-            _ => return Ok(ProviderResult::not_found(self.name(), self.display_name())),
+        assert_eq!(ClineProvider::task_model(dir.path()).as_deref(), Some("claude-opus-4"));
+    }
+
+    #[test]
+    fn requests_without_a_cost_are_estimated_individually() {
+        let dir = tempfile::tempdir().unwrap();
+        let request = |tokens_in: u64, cost: Option<f64>| {
+            let info = serde_json::json!({ "tokensIn": tokens_in, "tokensOut": 1000, "cost": cost });
+            serde_json::json!({ "ts": 1_750_000_000_000_i64, "say": "api_req_started", "text": info.to_string() })
         };
+        let path = dir.path().join("ui_messages.json");
+        fs::write(&path, serde_json::json!([request(10_000, Some(0.5)), request(10_000, None)]).to_string()).unwrap();
 
-        let ranges = Self::get_time_ranges();
-        let mut stats = UsageStats::default();
+        let mut costs = Vec::new();
+        assert!(ClineProvider::process_ui_messages(&path, Some("gpt-4o"), &mut |usage, timestamp, _| {
+            assert!(timestamp.is_some());
+            costs.push(usage.estimated_cost);
+        }));
 
-        Self::process_tasks_dir(&tasks_dir, &mut stats, &ranges);
+        assert_eq!(costs[0], 0.5);
+        let expected = calculate_cost_with_cache(10_000, 1000, 0, 0, Some("gpt-4o"));
+        assert!(expected > 0.0);
+        assert_eq!(costs[1], expected);
+    }
 
-        // Calculate costs if not already set
-        if stats.total.estimated_cost == 0.0 && stats.total.total_tokens() > 0 {
-            stats.today.estimated_cost = calculate_cost(stats.today.input_tokens, stats.today.output_tokens, Some("claude-sonnet-4"));
-            stats.this_week.estimated_cost = calculate_cost(stats.this_week.input_tokens, stats.this_week.output_tokens, Some("claude-sonnet-4"));
-            stats.this_month.estimated_cost = calculate_cost(stats.this_month.input_tokens, stats.this_month.output_tokens, Some("claude-sonnet-4"));
-            stats.total.estimated_cost = calculate_cost(stats.total.input_tokens, stats.total.output_tokens, Some("claude-sonnet-4"));
-        }
+    #[test]
+    fn requests_take_the_model_around_them_and_keep_zero_costs() {
+        let dir = tempfile::tempdir().unwrap();
+        let message = |say: &str, model: Option<&str>, cost: Option<f64>| {
+            let info = serde_json::json!({ "tokensIn": 10_000, "tokensOut": 1000, "cost": cost });
+            let mut message = serde_json::json!({ "ts": 1_750_000_000_000_i64, "say": say, "text": info.to_string() });
+            if let Some(model) = model {
+                message["modelInfo"] = serde_json::json!({ "providerId": "openrouter", "modelId": model });
+            }
+            message
+        };
+        let path = dir.path().join("ui_messages.json");
+        fs::write(
+            &path,
+            serde_json::json!([
+                message("api_req_started", None, None),
+                message("text", Some("gpt-4o"), None),
+                message("api_req_started", Some("claude-opus-4"), None),
+                message("api_req_started", None, Some(0.0)),
+            ])
+            .to_string(),
+        )
+        .unwrap();
 
-        Ok(ProviderResult::active(
-            self.name(),
-            self.display_name(),
-            stats,
-            &tasks_dir.to_string_lossy(),
-        ))
+        let mut requests = Vec::new();
+        assert!(ClineProvider::process_ui_messages(&path, Some("gpt-4o-mini"), &mut |usage, _, model| {
+            requests.push((model.map(str::to_string), usage.estimated_cost));
+        }));
+
+        let models: Vec<Option<&str>> = requests.iter().map(|(m, _)| m.as_deref()).collect();
+        assert_eq!(models, [Some("gpt-4o"), Some("claude-opus-4"), Some("claude-opus-4")]);
+        assert_eq!(requests[0].1, calculate_cost_with_cache(10_000, 1000, 0, 0, Some("gpt-4o")));
+        assert_eq!(requests[1].1, calculate_cost_with_cache(10_000, 1000, 0, 0, Some("claude-opus-4")));
+        assert_eq!(requests[2].1, 0.0);
     }
 }
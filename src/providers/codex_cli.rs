@@ -0,0 +1,463 @@
+//! OpenAI Codex CLI Provider
+//! Reads rollout transcripts from ~/.codex/sessions/YYYY/MM/DD/rollout-*.jsonl
+//!
+//! Each `token_count` event carries the usage of the last model call and a snapshot of the
+//! account's rate-limit windows. No API key is needed.
+
+use super::Provider;
+use crate::types::{ProviderResult, RateLimit, TimeRange, UsageBreakdown, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, codex_cli};
+use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost_with_cache;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Local, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// Model assumed for pricing when a rollout doesn't name one
+const DEFAULT_MODEL: &str = "gpt-5";
+
+#[derive(Debug, Deserialize)]
+struct RolloutLine {
+    timestamp: Option<String>,
+    #[serde(rename = "type")]
+    line_type: Option<String>,
+    payload: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionMeta {
+    cwd: Option<String>,
+    model: Option<String>,
+    git: Option<GitInfo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct GitInfo {
+    branch: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TurnContext {
+    cwd: Option<String>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventMsg {
+    #[serde(rename = "type")]
+    event_type: Option<String>,
+    info: Option<TokenCountInfo>,
+    rate_limits: Option<RateLimitSnapshot>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenCountInfo {
+    total_token_usage: Option<TokenUsage>,
+    last_token_usage: Option<TokenUsage>,
+}
+
+/// Codex token counts. `input_tokens` includes `cached_input_tokens`,
+/// and `output_tokens` includes `reasoning_output_tokens`.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+struct TokenUsage {
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    output_tokens: u64,
+    reasoning_output_tokens: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitSnapshot {
+    primary: Option<RateLimitWindow>,
+    secondary: Option<RateLimitWindow>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitWindow {
+    used_percent: Option<f64>,
+    window_minutes: Option<u64>,
+    /// Seconds since the epoch (current versions)
+    resets_at: Option<i64>,
+    /// Seconds after the event (older versions)
+    resets_in_seconds: Option<i64>,
+}
+
+/// Session context in effect at a point of the rollout
+#[derive(Debug, Default)]
+struct SessionContext {
+    cwd: Option<String>,
+    git_branch: Option<String>,
+    model: Option<String>,
+}
+
+/// Most recent rate-limit snapshot across all rollouts
+#[derive(Default)]
+struct LatestRateLimits {
+    observed_at: Option<DateTime<Utc>>,
+    windows: Vec<RateLimit>,
+}
+
+pub struct CodexCliProvider;
+
+impl CodexCliProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Session directories, including any configured extras
+    fn sessions_dirs() -> Vec<PathBuf> {
+        config::get().data_paths(
+            "codex-cli",
+            codex_cli::sessions_dir().into_iter().chain(codex_cli::archived_sessions_dir()),
+        )
+    }
+
+    fn rollout_files(sessions_dir: &Path) -> Vec<PathBuf> {
+        let pattern = sessions_dir.join("**").join("rollout-*.jsonl");
+        glob::glob(&pattern.to_string_lossy())
+            .map(|paths| paths.flatten().collect())
+            .unwrap_or_default()
+    }
+
+    fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+
+    /// Call `f` with every model call in every rollout, and collect the latest rate-limit snapshot
+    fn walk_rollouts(
+        latest: &mut LatestRateLimits,
+        f: &mut impl FnMut(&SessionContext, &UsageData, Option<DateTime<Utc>>),
+    ) -> Vec<PathBuf> {
+        let sessions_dirs: Vec<PathBuf> = Self::sessions_dirs().into_iter().filter(|p| p.exists()).collect();
+
+        for sessions_dir in &sessions_dirs {
+            for rollout in Self::rollout_files(sessions_dir) {
+                Self::process_rollout(&rollout, latest, f);
+            }
+        }
+
+        sessions_dirs
+    }
+
+    fn process_rollout(
+        path: &Path,
+        latest: &mut LatestRateLimits,
+        f: &mut impl FnMut(&SessionContext, &UsageData, Option<DateTime<Utc>>),
+    ) {
+        let Ok(file) = File::open(path) else {
+            return;
+        };
+
+        let mut context = SessionContext::default();
+        let mut previous_total: Option<TokenUsage> = None;
+
+        for line in BufReader::new(file).lines().map_while(Result::ok) {
+            let Ok(entry) = serde_json::from_str::<RolloutLine>(&line) else {
+                continue;
+            };
+            let Some(payload) = entry.payload else {
+                continue;
+            };
+            let timestamp = entry.timestamp.as_deref().and_then(Self::parse_timestamp);
+
+            match entry.line_type.as_deref() {
+                Some("session_meta") => {
+                    if let Ok(meta) = serde_json::from_value::<SessionMeta>(payload) {
+                        context.cwd = meta.cwd.or(context.cwd);
+                        context.model = meta.model.or(context.model);
+                        context.git_branch = meta.git.and_then(|g| g.branch).or(context.git_branch);
+                    }
+                }
+                Some("turn_context") => {
+                    if let Ok(turn) = serde_json::from_value::<TurnContext>(payload) {
+                        context.cwd = turn.cwd.or(context.cwd);
+                        context.model = turn.model.or(context.model);
+                    }
+                }
+                Some("event_msg") => {
+                    let Ok(event) = serde_json::from_value::<EventMsg>(payload) else {
+                        continue;
+                    };
+                    if event.event_type.as_deref() != Some("token_count") {
+                        continue;
+                    }
+
+                    if let Some(ref snapshot) = event.rate_limits {
+                        Self::record_rate_limits(snapshot, timestamp, latest);
+                    }
+
+                    let Some(info) = event.info else {
+                        continue;
+                    };
+                    // Codex repeats the last token_count when only the rate limits change
+                    if info.total_token_usage.is_some() && info.total_token_usage == previous_total {
+                        continue;
+                    }
+                    let last = info.last_token_usage.or_else(|| {
+                        let total = info.total_token_usage.as_ref()?;
+                        let previous = previous_total.clone().unwrap_or_default();
+                        Some(TokenUsage {
+                            input_tokens: total.input_tokens.saturating_sub(previous.input_tokens),
+                            cached_input_tokens: total.cached_input_tokens.saturating_sub(previous.cached_input_tokens),
+                            output_tokens: total.output_tokens.saturating_sub(previous.output_tokens),
+                            reasoning_output_tokens: total
+                                .reasoning_output_tokens
+                                .saturating_sub(previous.reasoning_output_tokens),
+                        })
+                    });
+                    previous_total = info.total_token_usage.or(previous_total);
+
+                    if let Some(last) = last {
+                        let usage = Self::to_usage_data(&last, context.model.as_deref());
+                        if usage.total_tokens() > 0 {
+                            f(&context, &usage, timestamp);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn to_usage_data(tokens: &TokenUsage, model: Option<&str>) -> UsageData {
+        let mut usage = UsageData::new();
+        usage.input_tokens = tokens.input_tokens.saturating_sub(tokens.cached_input_tokens);
+        usage.cache_read_tokens = tokens.cached_input_tokens;
+        usage.output_tokens = tokens.output_tokens;
+        usage.request_count = 1;
+        usage.estimated_cost = calculate_cost_with_cache(
+            usage.input_tokens,
+            usage.output_tokens,
+            usage.cache_read_tokens,
+            0,
+            Some(model.unwrap_or(DEFAULT_MODEL)),
+        );
+        usage
+    }
+
+    fn record_rate_limits(
+        snapshot: &RateLimitSnapshot,
+        timestamp: Option<DateTime<Utc>>,
+        latest: &mut LatestRateLimits,
+    ) {
+        if latest.observed_at.is_some() && timestamp < latest.observed_at {
+            return;
+        }
+
+        let windows: Vec<RateLimit> = [("primary", &snapshot.primary), ("secondary", &snapshot.secondary)]
+            .into_iter()
+            .filter_map(|(name, window)| {
+                let window = window.as_ref()?;
+                let resets_at = window
+                    .resets_at
+                    .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+                    .or_else(|| Some(timestamp? + chrono::Duration::seconds(window.resets_in_seconds?)));
+                Some(RateLimit {
+                    name: name.to_string(),
+                    used_percent: window.used_percent?,
+                    window_minutes: window.window_minutes,
+                    resets_at,
+                    observed_at: timestamp,
+                })
+            })
+            .collect();
+
+        if !windows.is_empty() {
+            latest.observed_at = timestamp;
+            latest.windows = windows;
+        }
+    }
+
+    fn rate_limits_note(latest: &LatestRateLimits) -> Option<String> {
+        let observed_at = latest.observed_at?;
+        let windows: Vec<String> = latest
+            .windows
+            .iter()
+            .map(|w| match w.window_label() {
+                Some(window) => format!("{} ({}) {:.0}% used", w.name, window, w.used_percent),
+                None => format!("{} {:.0}% used", w.name, w.used_percent),
+            })
+            .collect();
+        Some(format!(
+            "Rate limits as of {}: {}",
+            observed_at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+            windows.join(", ")
+        ))
+    }
+
+    /// Usage across all rollouts, pushing each model call to `records` when given
+    fn scan(&self, mut records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut by_model: HashMap<String, UsageStats> = HashMap::new();
+        let mut latest = LatestRateLimits::default();
+
+        let sessions_dirs = Self::walk_rollouts(&mut latest, &mut |context, usage, timestamp| {
+            if let (Some(records), Some(timestamp)) = (records.as_deref_mut(), timestamp) {
+                records.push(UsageRecord {
+                    provider: self.name(),
+                    timestamp,
+                    cwd: context.cwd.as_ref().map(PathBuf::from),
+                    git_branch: context.git_branch.clone().filter(|b| !b.is_empty()),
+                    model: context.model.clone(),
+                    usage: usage.clone(),
+                });
+            }
+
+            stats.add_at(usage, timestamp, &ranges);
+            by_model
+                .entry(context.model.clone().unwrap_or_else(|| "unknown".to_string()))
+                .or_default()
+                .add_at(usage, timestamp, &ranges);
+        });
+
+        if sessions_dirs.is_empty() {
+            return ProviderResult::not_found(self.name(), self.display_name());
+        }
+
+        let breakdown = UsageBreakdown::from_slices("model", by_model);
+
+        let mut result = ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &paths::join_paths(&sessions_dirs),
+        )
+        .with_breakdown(breakdown);
+        if let Some(note) = Self::rate_limits_note(&latest) {
+            result = result.with_note(note);
+        }
+        result.with_rate_limits(latest.windows)
+    }
+}
+
+#[async_trait]
+impl Provider for CodexCliProvider {
+    fn name(&self) -> &'static str {
+        "codex-cli"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Codex CLI"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::sessions_dirs().iter().any(|p| p.exists())
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        Self::sessions_dirs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        Ok(self.scan(None))
+    }
+
+    fn has_usage_records(&self) -> bool {
+        true
+    }
+
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
+
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        Ok((result, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn token_count(total: Option<(u64, u64, u64)>, last: Option<(u64, u64, u64)>) -> String {
+        let usage = |(input, cached, output): (u64, u64, u64)| {
+            serde_json::json!({ "input_tokens": input, "cached_input_tokens": cached, "output_tokens": output })
+        };
+        serde_json::json!({
+            "timestamp": "2025-06-01T12:00:00Z",
+            "type": "event_msg",
+            "payload": {
+                "type": "token_count",
+                "info": { "total_token_usage": total.map(usage), "last_token_usage": last.map(usage) },
+            },
+        })
+        .to_string()
+    }
+
+    /// Usage of every model call in a rollout made of `lines`
+    fn calls(lines: &[String]) -> Vec<(Option<String>, UsageData)> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout-test.jsonl");
+        let mut file = File::create(&path).unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+
+        let mut calls = Vec::new();
+        CodexCliProvider::process_rollout(&path, &mut LatestRateLimits::default(), &mut |context, usage, _| {
+            calls.push((context.model.clone(), usage.clone()));
+        });
+        calls
+    }
+
+    #[test]
+    fn diffs_cumulative_totals_when_last_usage_is_missing() {
+        let calls = calls(&[
+            r#"{"type":"turn_context","payload":{"cwd":"/work/app","model":"gpt-5-codex"}}"#.to_string(),
+            token_count(Some((1000, 200, 100)), None),
+            token_count(Some((2500, 1200, 160)), None),
+        ]);
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].0.as_deref(), Some("gpt-5-codex"));
+        // Cached input is billed separately, so it's taken out of input
+        assert_eq!((calls[0].1.input_tokens, calls[0].1.cache_read_tokens, calls[0].1.output_tokens), (800, 200, 100));
+        assert_eq!((calls[1].1.input_tokens, calls[1].1.cache_read_tokens, calls[1].1.output_tokens), (500, 1000, 60));
+    }
+
+    #[test]
+    fn skips_repeated_totals() {
+        // Codex re-sends the last token_count when only the rate limits change
+        let calls = calls(&[
+            token_count(Some((1000, 0, 100)), Some((1000, 0, 100))),
+            token_count(Some((1000, 0, 100)), Some((1000, 0, 100))),
+            token_count(Some((1600, 0, 150)), Some((600, 0, 50))),
+        ]);
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!((calls[1].1.input_tokens, calls[1].1.output_tokens), (600, 50));
+    }
+
+    #[test]
+    fn prefers_last_usage_over_the_difference() {
+        let calls = calls(&[
+            token_count(Some((1000, 0, 100)), Some((1000, 0, 100))),
+            // A compacted context: the reported last call beats the (shrunken) difference
+            token_count(Some((1200, 0, 120)), Some((900, 0, 20))),
+        ]);
+
+        assert_eq!(calls.len(), 2);
+        assert_eq!((calls[1].1.input_tokens, calls[1].1.output_tokens), (900, 20));
+        assert_eq!(calls[1].1.request_count, 1);
+    }
+}
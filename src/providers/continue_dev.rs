@@ -0,0 +1,272 @@
+//! Continue Provider
+//! Reads Continue's development data from ~/.continue/dev_data/<schema>/tokensGenerated.jsonl
+//!
+//! Every model call logs one `tokensGenerated` event with the model, its provider, and the
+//! prompt and generated token counts. The session index (~/.continue/sessions/sessions.json)
+//! adds how many chats were held.
+
+use super::Provider;
+use crate::types::{ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, continue_dev};
+use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// One `tokensGenerated` event; schema 0.1.0 used snake_case names
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TokensGenerated {
+    model: Option<String>,
+    provider: Option<String>,
+    #[serde(alias = "prompt_tokens")]
+    prompt_tokens: Option<u64>,
+    #[serde(alias = "generated_tokens")]
+    generated_tokens: Option<u64>,
+    timestamp: Option<String>,
+}
+
+/// Entry of `sessions/sessions.json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionInfo {
+    /// Milliseconds since the epoch, stored as a string
+    date_created: Option<serde_json::Value>,
+}
+
+pub struct ContinueProvider;
+
+/// Per-model and per-provider slices
+#[derive(Default)]
+struct Breakdowns {
+    by_model: HashMap<String, UsageStats>,
+    by_provider: HashMap<String, UsageStats>,
+}
+
+impl ContinueProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Dev data directories, including any configured extras
+    fn dev_data_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("continue", continue_dev::dev_data_dir())
+    }
+
+    /// Chat session directories (`<sessionId>.json` and the `sessions.json` index), next to
+    /// each dev data directory
+    fn sessions_dirs() -> Vec<PathBuf> {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for dir in Self::dev_data_dirs() {
+            let sessions = dir.parent().unwrap_or(&dir).join("sessions");
+            if !dirs.contains(&sessions) {
+                dirs.push(sessions);
+            }
+        }
+        dirs
+    }
+
+    /// Event logs of every schema version, plus the 0.1.0 file at the top level
+    fn token_logs(dev_data_dir: &Path) -> Vec<PathBuf> {
+        let mut logs: Vec<PathBuf> = glob::glob(&dev_data_dir.join("*").join("tokensGenerated.jsonl").to_string_lossy())
+            .map(|paths| paths.flatten().collect())
+            .unwrap_or_default();
+        let legacy = dev_data_dir.join("tokens_generated.jsonl");
+        if legacy.exists() {
+            logs.push(legacy);
+        }
+        logs
+    }
+
+    fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(s).ok().map(|dt| dt.with_timezone(&Utc))
+    }
+
+    fn process_token_log(
+        path: &Path,
+        stats: &mut UsageStats,
+        breakdowns: &mut Breakdowns,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> Result<()> {
+        let reader = BufReader::new(File::open(path)?);
+
+        for line in reader.lines().map_while(|l| l.ok()) {
+            let Ok(event) = serde_json::from_str::<TokensGenerated>(&line) else {
+                continue;
+            };
+
+            let mut usage = UsageData::new();
+            usage.input_tokens = event.prompt_tokens.unwrap_or(0);
+            usage.output_tokens = event.generated_tokens.unwrap_or(0);
+            usage.request_count = 1;
+            usage.estimated_cost = calculate_cost(usage.input_tokens, usage.output_tokens, event.model.as_deref());
+
+            let timestamp = event.timestamp.as_deref().and_then(Self::parse_timestamp);
+            stats.add_at(&usage, timestamp, ranges);
+
+            let model = event.model.unwrap_or_else(|| "unknown".to_string());
+            breakdowns.by_model.entry(model).or_default().add_at(&usage, timestamp, ranges);
+            let provider = event.provider.unwrap_or_else(|| "unknown".to_string());
+            breakdowns.by_provider.entry(provider).or_default().add_at(&usage, timestamp, ranges);
+        }
+
+        Ok(())
+    }
+
+    /// Creation times of the chats in a session index
+    fn session_starts(index: &Path) -> Option<Vec<Option<DateTime<Utc>>>> {
+        let sessions: Vec<SessionInfo> = serde_json::from_str(&fs::read_to_string(index).ok()?).ok()?;
+        Some(
+            sessions
+                .iter()
+                .map(|s| {
+                    let ms = match s.date_created.as_ref()? {
+                        serde_json::Value::String(s) => s.parse::<i64>().ok()?,
+                        value => value.as_i64()?,
+                    };
+                    Utc.timestamp_millis_opt(ms).single()
+                })
+                .collect(),
+        )
+    }
+
+    fn sessions_note(starts: &[Option<DateTime<Utc>>], ranges: &(TimeRange, TimeRange, TimeRange)) -> String {
+        let this_week = starts.iter().flatten().filter(|t| ranges.1.contains(**t)).count();
+        format!("{} chat sessions ({} this week)", starts.len(), this_week)
+    }
+}
+
+#[async_trait]
+impl Provider for ContinueProvider {
+    fn name(&self) -> &'static str {
+        "continue"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Continue"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::dev_data_dirs().iter().any(|p| p.exists())
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        Self::dev_data_dirs()
+            .into_iter()
+            .chain(Self::sessions_dirs())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let dev_data_dirs: Vec<PathBuf> = Self::dev_data_dirs().into_iter().filter(|p| p.exists()).collect();
+        if dev_data_dirs.is_empty() {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        }
+
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+
+        for dir in &dev_data_dirs {
+            for log in Self::token_logs(dir) {
+                let _ = Self::process_token_log(&log, &mut stats, &mut breakdowns, &ranges);
+            }
+        }
+
+        let mut breakdown = UsageBreakdown::from_slices("model", breakdowns.by_model);
+        breakdown.extend(UsageBreakdown::from_slices("provider", breakdowns.by_provider));
+
+        let mut result = ProviderResult::active(self.name(), self.display_name(), stats, &paths::join_paths(&dev_data_dirs))
+            .with_breakdown(breakdown);
+        let indexes: Vec<Vec<Option<DateTime<Utc>>>> = Self::sessions_dirs()
+            .iter()
+            .filter_map(|dir| Self::session_starts(&dir.join("sessions.json")))
+            .collect();
+        if !indexes.is_empty() {
+            result = result.with_note(Self::sessions_note(&indexes.concat(), &ranges));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_current_and_legacy_token_logs() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now().to_rfc3339();
+        fs::create_dir(dir.path().join("0.2.0")).unwrap();
+        fs::write(
+            dir.path().join("0.2.0").join("tokensGenerated.jsonl"),
+            format!(
+                "{}\nnot json\n{}\n",
+                serde_json::json!({ "model": "gpt-4o", "provider": "openai", "promptTokens": 1000, "generatedTokens": 100, "timestamp": now }),
+                serde_json::json!({ "model": "gpt-4o", "provider": "openai", "promptTokens": 500, "generatedTokens": 50, "timestamp": "2024-01-01T00:00:00.000Z" }),
+            ),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("tokens_generated.jsonl"),
+            format!(
+                "{}\n",
+                serde_json::json!({ "model": "claude-3-5-sonnet", "provider": "anthropic", "prompt_tokens": 200, "generated_tokens": 20, "timestamp": now })
+            ),
+        )
+        .unwrap();
+
+        let logs = ContinueProvider::token_logs(dir.path());
+        assert_eq!(logs.len(), 2);
+        let ranges = get_local_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        for log in &logs {
+            ContinueProvider::process_token_log(log, &mut stats, &mut breakdowns, &ranges).unwrap();
+        }
+
+        assert_eq!((stats.total.input_tokens, stats.total.output_tokens), (1700, 170));
+        assert_eq!((stats.today.input_tokens, stats.today.request_count), (1200, 2));
+        assert_eq!(breakdowns.by_model["gpt-4o"].total.request_count, 2);
+        assert_eq!(breakdowns.by_provider["anthropic"].total.input_tokens, 200);
+        assert!(stats.total.estimated_cost > 0.0);
+    }
+
+    #[test]
+    fn session_starts_accept_string_and_numeric_dates() {
+        let dir = tempfile::tempdir().unwrap();
+        let index = dir.path().join("sessions.json");
+        let now = Utc::now().timestamp_millis();
+        fs::write(
+            &index,
+            serde_json::json!([
+                { "sessionId": "a", "title": "One", "dateCreated": now.to_string() },
+                { "sessionId": "b", "title": "Two", "dateCreated": now },
+                { "sessionId": "c", "title": "Three", "dateCreated": "yesterday" },
+            ])
+            .to_string(),
+        )
+        .unwrap();
+
+        let starts = ContinueProvider::session_starts(&index).unwrap();
+        let expected = Utc.timestamp_millis_opt(now).single();
+        assert_eq!(starts, vec![expected, expected, None]);
+        assert_eq!(
+            ContinueProvider::sessions_note(&starts, &get_local_time_ranges()),
+            "3 chat sessions (2 this week)"
+        );
+        assert!(ContinueProvider::session_starts(&dir.path().join("missing.json")).is_none());
+    }
+}
@@ -1,19 +1,25 @@
 //! Cursor Provider
 //! Reads usage data from Cursor's SQLite state database
-//! Uses snapshot strategy to avoid SQLITE_BUSY errors
+//! Uses read-only/backup snapshot strategy to avoid SQLITE_BUSY errors
+//!
+//! Token usage lives in the global database as `bubbleId:<composerId>:<bubbleId>` entries.
+//! Each workspace database lists its composers, which ties usage to the workspace folder.
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
 use crate::utils::db::with_db_snapshot;
-use crate::utils::paths::cursor;
+use crate::utils::paths::{self, cursor, vscode};
 use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use rusqlite::Connection;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct ComposerData {
@@ -44,6 +50,33 @@ struct ChatMessage {
     role: Option<String>,
 }
 
+/// `workspace.json` of a workspace storage directory
+#[derive(Debug, Deserialize)]
+struct WorkspaceInfo {
+    folder: Option<String>,
+}
+
+/// `composer.composerData` of a workspace database
+#[derive(Debug, Deserialize)]
+struct WorkspaceComposers {
+    #[serde(rename = "allComposers", default)]
+    all_composers: Vec<ComposerRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ComposerRef {
+    #[serde(rename = "composerId")]
+    composer_id: Option<String>,
+}
+
+/// Usage of one bubble (model reply)
+struct Bubble {
+    composer_id: Option<String>,
+    model: Option<String>,
+    usage: UsageData,
+    timestamp: Option<DateTime<Utc>>,
+}
+
 pub struct CursorProvider;
 
 impl CursorProvider {
@@ -55,6 +88,11 @@ impl CursorProvider {
         get_local_time_ranges()
     }
 
+    /// Global `state.vscdb` databases, including any configured extras
+    fn global_dbs() -> Vec<PathBuf> {
+        config::get().data_paths("cursor", cursor::global_storage())
+    }
+
     fn parse_timestamp_value(ts: &serde_json::Value) -> Option<DateTime<Utc>> {
         match ts {
             serde_json::Value::Number(n) => {
@@ -72,15 +110,19 @@ impl CursorProvider {
         }
     }
 
-    fn process_database(db_path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) -> Result<()> {
-        with_db_snapshot(db_path, |snapshot_path| {
-            let conn = Connection::open(snapshot_path)?;
-
+    /// Adds the database's usage to `stats`, and its bubbles to `bubbles` when given
+    fn process_database(
+        db_path: &Path,
+        stats: &mut UsageStats,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+        bubbles: Option<&mut Vec<Bubble>>,
+    ) -> Result<()> {
+        with_db_snapshot(db_path, |conn| {
             // Try ItemTable first
-            Self::query_item_table(&conn, stats, ranges)?;
+            Self::query_item_table(conn, stats, ranges)?;
 
             // Try cursorDiskKV table
-            Self::query_cursor_disk_kv(&conn, stats, ranges)?;
+            Self::query_cursor_disk_kv(conn, stats, ranges, bubbles)?;
 
             Ok(())
         })?;
@@ -123,7 +165,12 @@ impl CursorProvider {
         Ok(())
     }
 
-    fn query_cursor_disk_kv(conn: &Connection, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) -> Result<()> {
+    fn query_cursor_disk_kv(
+        conn: &Connection,
+        stats: &mut UsageStats,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+        mut bubbles: Option<&mut Vec<Bubble>>,
+    ) -> Result<()> {
         // Check if table exists
         let table_exists: bool = conn
             .query_row(
@@ -165,51 +212,48 @@ impl CursorProvider {
             Ok((key, value))
         })?;
 
-        for row in rows2.flatten() {
-            Self::process_bubble_data_str(&row.1, stats, ranges);
+        for (key, value) in rows2.flatten() {
+            let Some(bubble) = Self::parse_bubble(&key, &value) else {
+                continue;
+            };
+            stats.add_at(&bubble.usage, bubble.timestamp, ranges);
+            if let Some(bubbles) = bubbles.as_deref_mut() {
+                bubbles.push(bubble);
+            }
         }
 
         Ok(())
     }
 
-    fn process_bubble_data_str(value_str: &str, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
-        // Parse as generic JSON first to extract tokenCount
-        if let Ok(json_val) = serde_json::from_str::<serde_json::Value>(value_str) {
-            if let Some(tc) = json_val.get("tokenCount") {
-                let input_tokens = tc.get("inputTokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-                let output_tokens = tc.get("outputTokens")
-                    .and_then(|v| v.as_u64())
-                    .unwrap_or(0);
-
-                if input_tokens > 0 || output_tokens > 0 {
-                    let mut usage = UsageData::new();
-                    usage.input_tokens = input_tokens;
-                    usage.output_tokens = output_tokens;
-                    usage.request_count = 1;
-                    stats.total.add(&usage);
-
-                    // Try to get timestamp from createdAt
-                    let timestamp = json_val.get("createdAt")
-                        .and_then(|v| v.as_str())
-                        .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
-                        .map(|dt| dt.with_timezone(&Utc));
-
-                    if let Some(ts) = timestamp {
-                        if ranges.0.contains(ts) {
-                            stats.today.add(&usage);
-                        }
-                        if ranges.1.contains(ts) {
-                            stats.this_week.add(&usage);
-                        }
-                        if ranges.2.contains(ts) {
-                            stats.this_month.add(&usage);
-                        }
-                    }
-                }
-            }
+    /// Token usage of a `bubbleId:<composerId>:<bubbleId>` entry, if it has any
+    fn parse_bubble(key: &str, value_str: &str) -> Option<Bubble> {
+        let json_val = serde_json::from_str::<serde_json::Value>(value_str).ok()?;
+        let tc = json_val.get("tokenCount")?;
+        let input_tokens = tc.get("inputTokens").and_then(|v| v.as_u64()).unwrap_or(0);
+        let output_tokens = tc.get("outputTokens").and_then(|v| v.as_u64()).unwrap_or(0);
+        if input_tokens == 0 && output_tokens == 0 {
+            return None;
         }
+
+        let model = json_val
+            .get("modelInfo")
+            .and_then(|m| m.get("modelName"))
+            .and_then(|m| m.as_str())
+            .filter(|m| !m.is_empty() && *m != "default")
+            .map(str::to_string);
+
+        let mut usage = UsageData::new();
+        usage.input_tokens = input_tokens;
+        usage.output_tokens = output_tokens;
+        usage.request_count = 1;
+        usage.estimated_cost = calculate_cost(input_tokens, output_tokens, model.as_deref());
+
+        Some(Bubble {
+            composer_id: key.split(':').nth(1).map(str::to_string),
+            model,
+            usage,
+            timestamp: json_val.get("createdAt").and_then(Self::parse_timestamp_value),
+        })
     }
 
     fn process_key_value_str(_key: &str, value_str: &str, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
@@ -229,7 +273,7 @@ impl CursorProvider {
                 // Try to get timestamp (can be number or string)
                 let timestamp = data.created_at.as_ref()
                     .or(data.updated_at.as_ref())
-                    .and_then(|ts| Self::parse_timestamp_value(ts));
+                    .and_then(Self::parse_timestamp_value);
 
                 if let Some(ts) = timestamp {
                     if ranges.0.contains(ts) {
@@ -261,6 +305,49 @@ impl CursorProvider {
         }
     }
 
+    /// Workspace folder of every composer listed in the workspace databases
+    fn composer_workspaces(workspace_dir: &Path) -> HashMap<String, PathBuf> {
+        let mut workspaces = HashMap::new();
+        let Ok(entries) = fs::read_dir(workspace_dir) else {
+            return workspaces;
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let folder = fs::read_to_string(path.join("workspace.json"))
+                .ok()
+                .and_then(|content| serde_json::from_str::<WorkspaceInfo>(&content).ok())
+                .and_then(|info| info.folder)
+                .and_then(|uri| vscode::folder_path(&uri));
+            let Some(folder) = folder else {
+                continue;
+            };
+            let db_path = path.join("state.vscdb");
+            if !db_path.exists() {
+                continue;
+            }
+
+            let composers = with_db_snapshot(&db_path, |conn| {
+                let value: String = conn.query_row(
+                    "SELECT value FROM ItemTable WHERE key = 'composer.composerData'",
+                    [],
+                    |row| {
+                        row.get::<_, String>(0)
+                            .or_else(|_| row.get::<_, Vec<u8>>(0).map(|b| String::from_utf8_lossy(&b).to_string()))
+                    },
+                )?;
+                Ok(serde_json::from_str::<WorkspaceComposers>(&value)?)
+            });
+            if let Ok(composers) = composers {
+                for composer_id in composers.all_composers.into_iter().filter_map(|c| c.composer_id) {
+                    workspaces.insert(composer_id, folder.clone());
+                }
+            }
+        }
+
+        workspaces
+    }
+
     fn get_recent_workspaces(workspace_dir: &Path, limit: usize) -> Vec<std::path::PathBuf> {
         let mut workspaces: Vec<(std::path::PathBuf, std::time::SystemTime)> = Vec::new();
 
@@ -280,52 +367,29 @@ impl CursorProvider {
             }
         }
 
-        workspaces.sort_by(|a, b| b.1.cmp(&a.1));
+        workspaces.sort_by_key(|w| std::cmp::Reverse(w.1));
         workspaces.into_iter().take(limit).map(|(p, _)| p).collect()
     }
-}
 
-#[async_trait]
-impl Provider for CursorProvider {
-    fn name(&self) -> &'static str {
-        "cursor"
-    }
-
-    fn display_name(&self) -> &'static str {
-        "Cursor"
-    }
-
-    async fn is_available(&self) -> bool {
-        cursor::global_storage().map(|p| p.exists()).unwrap_or(false)
-            || cursor::workspace_storage().map(|p| p.exists()).unwrap_or(false)
-    }
-
-    fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            cursor::global_storage().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            cursor::workspace_storage().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
-    }
-
-    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let global_db = cursor::global_storage();
+    /// Usage across the global and recent workspace databases. When `records` is given, each
+    /// bubble in the global databases is pushed with the workspace folder of its composer.
+    fn scan(&self, records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let global_dbs: Vec<PathBuf> = Self::global_dbs().into_iter().filter(|p| p.exists()).collect();
         let workspace_dir = cursor::workspace_storage();
 
-        let has_global = global_db.as_ref().map(|p| p.exists()).unwrap_or(false);
         let has_workspace = workspace_dir.as_ref().map(|p| p.exists()).unwrap_or(false);
 
-        if !has_global && !has_workspace {
-            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        if global_dbs.is_empty() && !has_workspace {
+            return ProviderResult::not_found(self.name(), self.display_name());
         }
 
         let ranges = Self::get_time_ranges();
         let mut stats = UsageStats::default();
+        let mut bubbles = Vec::new();
 
-        // Process global storage database
-        if let Some(ref db_path) = global_db {
-            if db_path.exists() {
-                let _ = Self::process_database(db_path, &mut stats, &ranges);
-            }
+        // Process global storage databases
+        for db_path in &global_dbs {
+            let _ = Self::process_database(db_path, &mut stats, &ranges, records.is_some().then_some(&mut bubbles));
         }
 
         // Process workspace storage databases (most recent first)
@@ -335,21 +399,88 @@ impl Provider for CursorProvider {
                 for workspace in workspaces {
                     let db_path = workspace.join("state.vscdb");
                     if db_path.exists() {
-                        let _ = Self::process_database(&db_path, &mut stats, &ranges);
+                        let _ = Self::process_database(&db_path, &mut stats, &ranges, None);
                     }
                 }
             }
         }
 
-        let data_source = global_db
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Cursor".to_string());
+        if let Some(records) = records {
+            let workspaces = workspace_dir.map(|dir| Self::composer_workspaces(&dir)).unwrap_or_default();
+            records.extend(bubbles.into_iter().filter_map(|bubble| {
+                Some(UsageRecord {
+                    provider: self.name(),
+                    timestamp: bubble.timestamp?,
+                    cwd: bubble.composer_id.and_then(|id| workspaces.get(&id).cloned()),
+                    git_branch: None,
+                    model: bubble.model,
+                    usage: bubble.usage,
+                })
+            }));
+        }
+
+        let data_source = if global_dbs.is_empty() {
+            cursor::global_storage()
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Cursor".to_string())
+        } else {
+            paths::join_paths(&global_dbs)
+        };
 
-        Ok(ProviderResult::active(
+        ProviderResult::active(
             self.name(),
             self.display_name(),
             stats,
             &data_source,
-        ))
+        )
+    }
+}
+
+#[async_trait]
+impl Provider for CursorProvider {
+    fn name(&self) -> &'static str {
+        "cursor"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Cursor"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::global_dbs().iter().any(|p| p.exists())
+            || cursor::workspace_storage().map(|p| p.exists()).unwrap_or(false)
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        let mut paths: Vec<String> = Self::global_dbs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.push(cursor::workspace_storage().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::LocalDb
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        Ok(self.scan(None))
+    }
+
+    fn has_usage_records(&self) -> bool {
+        true
+    }
+
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
+
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        Ok((result, records))
     }
 }
@@ -0,0 +1,159 @@
+//! DeepSeek Provider
+//! Uses the DeepSeek balance endpoint (`/user/balance`) to fetch the account's remaining
+//! prepaid credit. The API does not report spend or token usage.
+
+use super::Provider;
+use crate::types::{Balance, DataSourceType, ProviderResult, TimeRange, UsageStats};
+use crate::utils::config::{self, DEFAULT_LOW_BALANCE};
+use crate::utils::http;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct BalanceResponse {
+    is_available: Option<bool>,
+    #[serde(default)]
+    balance_infos: Vec<BalanceInfo>,
+}
+
+/// Amounts are decimal strings
+#[derive(Debug, Deserialize)]
+struct BalanceInfo {
+    currency: String,
+    total_balance: String,
+}
+
+/// DeepSeek API base URL (overridable with `providers.deepseek.base_url`)
+const DEFAULT_BASE_URL: &str = "https://api.deepseek.com";
+
+/// Rough exchange rate for the default low-balance threshold of CNY accounts
+const CNY_PER_USD: f64 = 7.0;
+
+pub struct DeepSeekProvider;
+
+impl DeepSeekProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_api_key() -> Option<String> {
+        std::env::var("DEEPSEEK_API_KEY").ok().filter(|k| !k.is_empty())
+    }
+
+    /// The account's balance, preferring USD when it holds several currencies. A configured
+    /// `threshold` is in the balance's currency; the default is converted from US dollars.
+    fn balance(response: &BalanceResponse, threshold: Option<f64>) -> Option<Balance> {
+        let info = response
+            .balance_infos
+            .iter()
+            .find(|b| b.currency.eq_ignore_ascii_case("USD"))
+            .or_else(|| response.balance_infos.first())?;
+
+        // An amount that doesn't parse is left out rather than read as an empty account
+        let remaining: f64 = info.total_balance.parse().ok()?;
+        let currency = info.currency.to_uppercase();
+        let threshold = threshold.unwrap_or(match currency.as_str() {
+            "CNY" => DEFAULT_LOW_BALANCE * CNY_PER_USD,
+            _ => DEFAULT_LOW_BALANCE,
+        });
+        Some(Balance { currency, remaining, total: None, low: remaining < threshold })
+    }
+}
+
+#[async_trait]
+impl Provider for DeepSeekProvider {
+    fn name(&self) -> &'static str {
+        "deepseek"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "DeepSeek"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::get_api_key().is_some()
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        vec!["DEEPSEEK_API_KEY environment variable".to_string()]
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::Api
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let api_key = match Self::get_api_key() {
+            Some(k) => k,
+            None => return Ok(ProviderResult::no_key(self.name(), self.display_name())),
+        };
+
+        let config = config::get();
+        let url = format!("{}/user/balance", config.base_url(self.name(), DEFAULT_BASE_URL));
+
+        let response = match http::get_json::<BalanceResponse>(&url, &api_key).await {
+            Ok(r) => r,
+            Err(e) => {
+                return Ok(ProviderResult::error(
+                    self.name(),
+                    self.display_name(),
+                    &format!("DeepSeek balance lookup failed: {}", e),
+                ))
+            }
+        };
+
+        let mut result = ProviderResult::active(self.name(), self.display_name(), UsageStats::default(), "DeepSeek API")
+            .with_note("DeepSeek reports the remaining balance only, not spend or tokens");
+
+        match Self::balance(&response, config.low_balance(self.name())) {
+            Some(balance) => result = result.with_balance(balance),
+            None if !response.balance_infos.is_empty() => {
+                result = result.with_note("DeepSeek returned a balance that couldn't be read")
+            }
+            None => {}
+        }
+        if response.is_available == Some(false) {
+            result = result.with_note("DeepSeek reports the balance is insufficient for API calls");
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(balances: &[(&str, &str)]) -> BalanceResponse {
+        let infos: Vec<_> = balances
+            .iter()
+            .map(|(currency, total)| serde_json::json!({ "currency": currency, "total_balance": total, "granted_balance": "0.00" }))
+            .collect();
+        serde_json::from_value(serde_json::json!({ "is_available": true, "balance_infos": infos })).unwrap()
+    }
+
+    #[test]
+    fn prefers_the_usd_balance() {
+        let balance = DeepSeekProvider::balance(&response(&[("CNY", "110.00"), ("USD", "15.50")]), None).unwrap();
+        assert_eq!(balance.currency, "USD");
+        assert_eq!(balance.remaining, 15.5);
+        assert!(!balance.low);
+
+        assert!(DeepSeekProvider::balance(&response(&[]), None).is_none());
+        assert!(DeepSeekProvider::balance(&response(&[("USD", "n/a")]), None).is_none());
+    }
+
+    #[test]
+    fn default_threshold_follows_the_currency() {
+        // 20 CNY is under $5, though more than 5 units
+        assert!(DeepSeekProvider::balance(&response(&[("CNY", "20.00")]), None).unwrap().low);
+        assert!(!DeepSeekProvider::balance(&response(&[("CNY", "50.00")]), None).unwrap().low);
+        assert!(DeepSeekProvider::balance(&response(&[("USD", "4.99")]), None).unwrap().low);
+    }
+
+    #[test]
+    fn configured_threshold_is_in_the_balance_currency() {
+        assert!(!DeepSeekProvider::balance(&response(&[("CNY", "20.00")]), Some(10.0)).unwrap().low);
+        assert!(DeepSeekProvider::balance(&response(&[("CNY", "20.00")]), Some(100.0)).unwrap().low);
+    }
+}
@@ -3,6 +3,7 @@
 
 use super::Provider;
 use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
+use crate::utils::config;
 use crate::utils::paths::gemini_cli;
 use crate::utils::time::get_local_time_ranges;
 use crate::utils::tokenizer::calculate_cost;
@@ -11,7 +12,7 @@ use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct GeminiLogEntry {
@@ -81,6 +82,11 @@ impl GeminiCLIProvider {
         get_local_time_ranges()
     }
 
+    /// Session history directories (`~/.gemini/tmp`), including any configured extras
+    fn tmp_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("gemini-cli", gemini_cli::tmp_dir())
+    }
+
     fn process_telemetry_log(path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
         if let Ok(content) = fs::read_to_string(path) {
             for line in content.lines() {
@@ -338,11 +344,16 @@ impl Provider for GeminiCLIProvider {
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
+        let mut paths: Vec<String> = Self::tmp_dirs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.extend([
             gemini_cli::conversations_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
             gemini_cli::telemetry_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
             gemini_cli::config_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        ]);
+        paths
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
@@ -355,8 +366,9 @@ impl Provider for GeminiCLIProvider {
         let has_telemetry = telemetry_path.as_ref().map(|p| p.exists()).unwrap_or(false);
         let has_conversations = conversations_dir.as_ref().map(|p| p.exists()).unwrap_or(false);
         let has_a2z_telemetry = a2z_telemetry.as_ref().map(|p| p.exists()).unwrap_or(false);
+        let tmp_dirs: Vec<PathBuf> = Self::tmp_dirs().into_iter().filter(|p| p.exists()).collect();
 
-        if !has_config && !has_telemetry && !has_conversations && !has_a2z_telemetry {
+        if !has_config && !has_telemetry && !has_conversations && !has_a2z_telemetry && tmp_dirs.is_empty() {
             return Ok(ProviderResult::not_found(self.name(), self.display_name()));
         }
 
@@ -371,11 +383,8 @@ impl Provider for GeminiCLIProvider {
         }
 
         // PRIORITY 2: Process native Gemini CLI session files
-        let tmp_dir = gemini_cli::tmp_dir();
-        if let Some(ref dir) = tmp_dir {
-            if dir.exists() {
-                Self::process_native_sessions(dir, &mut stats, &ranges);
-            }
+        for dir in &tmp_dirs {
+            Self::process_native_sessions(dir, &mut stats, &ranges);
         }
 
         // If we got data from a2zusage telemetry or native sessions, return it
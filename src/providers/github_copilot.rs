@@ -2,13 +2,23 @@
 //! Attempts to use gh CLI auth, falls back to manual token
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageStats};
-use crate::utils::paths::github_copilot;
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageStats};
+use crate::utils::config;
+use crate::utils::http;
+use crate::utils::paths::{github_copilot, vscode};
 use anyhow::Result;
 use async_trait::async_trait;
 use serde::Deserialize;
 use std::fs;
-use std::process::Command;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command;
+
+/// GitHub API base URL (overridable with `providers.github-copilot.base_url`)
+const DEFAULT_BASE_URL: &str = "https://api.github.com";
+
+/// Maximum time to wait for `gh auth token` before giving up on the gh CLI
+const GH_CLI_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(Debug, Deserialize)]
 struct CopilotUserResponse {
@@ -39,7 +49,7 @@ impl GitHubCopilotProvider {
         Self
     }
 
-    fn get_token() -> Option<String> {
+    async fn get_token() -> Option<String> {
         // Strategy 1: Check environment variables
         if let Ok(token) = std::env::var("A2Z_GITHUB_TOKEN") {
             return Some(token);
@@ -52,7 +62,7 @@ impl GitHubCopilotProvider {
         }
 
         // Strategy 2: Try gh CLI
-        if let Some(token) = Self::get_gh_cli_token() {
+        if let Some(token) = Self::get_gh_cli_token().await {
             return Some(token);
         }
 
@@ -64,11 +74,16 @@ impl GitHubCopilotProvider {
         None
     }
 
-    fn get_gh_cli_token() -> Option<String> {
-        let output = Command::new("gh")
+    async fn get_gh_cli_token() -> Option<String> {
+        // kill_on_drop ensures a hung gh process is terminated when the timeout fires
+        let child = Command::new("gh")
             .args(["auth", "token"])
-            .output()
-            .ok()?;
+            .stdin(Stdio::null())
+            .stderr(Stdio::null())
+            .kill_on_drop(true)
+            .output();
+
+        let output = tokio::time::timeout(GH_CLI_TIMEOUT, child).await.ok()?.ok()?;
 
         if output.status.success() {
             let token = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -93,11 +108,26 @@ impl GitHubCopilotProvider {
         hosts.github_com?.oauth_token
     }
 
+    /// VS Code family editors whose extension-host logs show Copilot running
+    fn host_editors() -> Vec<&'static str> {
+        github_copilot::vscode_logs()
+            .into_iter()
+            .filter(|(_, logs)| {
+                let pattern = logs.join("*").join("window*").join("exthost").join("GitHub.copilot*");
+                glob::glob(&pattern.to_string_lossy())
+                    .map(|mut matches| matches.next().is_some())
+                    .unwrap_or(false)
+            })
+            .map(|(editor, _)| editor)
+            .collect()
+    }
+
     async fn fetch_copilot_user(token: &str) -> Option<CopilotUserResponse> {
-        let client = reqwest::Client::new();
+        let client = http::client();
+        let base_url = config::get().base_url("github-copilot", DEFAULT_BASE_URL);
 
         let response = client
-            .get("https://api.github.com/copilot_internal/user")
+            .get(format!("{}/copilot_internal/user", base_url))
             .header("Authorization", format!("Bearer {}", token))
             .header("Accept", "application/json")
             .header("User-Agent", "a2zusage/1.0")
@@ -124,23 +154,34 @@ impl Provider for GitHubCopilotProvider {
     }
 
     async fn is_available(&self) -> bool {
-        Self::get_token().is_some()
+        Self::get_token().await.is_some()
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
+        let mut paths = vec![
             github_copilot::hosts_file().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            github_copilot::vscode_logs().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        ];
+        paths.extend(
+            vscode::installed(github_copilot::vscode_logs())
+                .iter()
+                .map(|(_, p)| p.to_string_lossy().to_string()),
+        );
+        paths
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::Api
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let token = match Self::get_token() {
+        let token = match Self::get_token().await {
             Some(t) => t,
             None => return Ok(ProviderResult::no_key(self.name(), self.display_name())),
         };
 
         let mut stats = UsageStats::default();
+        let editors = Self::host_editors();
+        let editors_note = format!("Copilot logs found in: {}", editors.join(", "));
 
         // Try the internal API for individual usage
         if let Some(user_response) = Self::fetch_copilot_user(&token).await {
@@ -151,15 +192,20 @@ impl Provider for GitHubCopilotProvider {
                 stats.total.request_count = usage_count;
             }
 
+            let result = ProviderResult::active(self.name(), self.display_name(), stats, "GitHub API");
+            return Ok(if editors.is_empty() { result } else { result.with_note(editors_note) });
+        }
+
+        // Fallback: check if Copilot is installed by looking for hosts.json or editor logs
+        if !editors.is_empty() {
             return Ok(ProviderResult::active(
                 self.name(),
                 self.display_name(),
                 stats,
-                "GitHub API",
-            ));
+                "Installed (API data unavailable)",
+            )
+            .with_note(editors_note));
         }
-
-        // Fallback: check if Copilot is installed by looking for hosts.json
         if github_copilot::hosts_file().map(|p| p.exists()).unwrap_or(false) {
             return Ok(ProviderResult::active(
                 self.name(),
@@ -0,0 +1,286 @@
+//! Goose Provider
+//! Reads session files from ~/.local/share/goose/sessions/<id>.jsonl
+//!
+//! The first line of a session is its metadata (working directory and token counts); the
+//! remaining lines are messages with creation times. Sessions don't record the model, and the
+//! one configured now may not be the one they ran with, so usage is priced at default rates.
+
+use super::Provider;
+use crate::types::{ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, goose};
+use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, TimeZone, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// First line of a session file
+#[derive(Debug, Deserialize)]
+struct SessionMetadata {
+    working_dir: Option<String>,
+    input_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    total_tokens: Option<u64>,
+    /// Totals including context that was summarized away
+    accumulated_input_tokens: Option<u64>,
+    accumulated_output_tokens: Option<u64>,
+    accumulated_total_tokens: Option<u64>,
+    message_count: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SessionMessage {
+    role: Option<String>,
+    /// Seconds since the epoch
+    created: Option<i64>,
+}
+
+/// Usage of one session, counted when it was last active
+struct Session {
+    working_dir: Option<String>,
+    usage: UsageData,
+    timestamp: Option<DateTime<Utc>>,
+}
+
+pub struct GooseProvider;
+
+impl GooseProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Session directories, including any configured extras
+    fn sessions_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("goose", goose::sessions_dir())
+    }
+
+    /// Model Goose is configured to use: `GOOSE_MODEL` from the environment, else config.yaml
+    fn configured_model() -> Option<String> {
+        if paths::alternate_home().is_none() {
+            if let Some(model) = std::env::var("GOOSE_MODEL").ok().filter(|m| !m.is_empty()) {
+                return Some(model);
+            }
+        }
+        let contents = fs::read_to_string(goose::config_file()?).ok()?;
+        contents
+            .lines()
+            .filter_map(|line| line.strip_prefix("GOOSE_MODEL:"))
+            .map(|value| value.trim().trim_matches(|c| c == '"' || c == '\'').to_string())
+            .find(|value| !value.is_empty())
+    }
+
+    fn session_files(sessions_dir: &Path) -> Vec<PathBuf> {
+        fs::read_dir(sessions_dir)
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| p.extension().map(|e| e == "jsonl").unwrap_or(false))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn read_session(path: &Path) -> Option<Session> {
+        let mut lines = BufReader::new(File::open(path).ok()?).lines().map_while(|l| l.ok());
+        let metadata: SessionMetadata = serde_json::from_str(&lines.next()?).ok()?;
+
+        let mut usage = UsageData::new();
+        let input = metadata.accumulated_input_tokens.or(metadata.input_tokens);
+        let output = metadata.accumulated_output_tokens.or(metadata.output_tokens);
+        match (input, output) {
+            (None, None) => {
+                // Only a total was recorded; keep it without inventing a split
+                usage.input_tokens = metadata.accumulated_total_tokens.or(metadata.total_tokens)?;
+            }
+            (input, output) => {
+                usage.input_tokens = input.unwrap_or(0);
+                usage.output_tokens = output.unwrap_or(0);
+            }
+        }
+        if usage.total_tokens() == 0 {
+            return None;
+        }
+
+        // Each assistant reply is one model call; the last message dates the session
+        let mut replies = 0;
+        let mut last_created = None;
+        for line in lines {
+            if let Ok(message) = serde_json::from_str::<SessionMessage>(&line) {
+                if message.role.as_deref() == Some("assistant") {
+                    replies += 1;
+                }
+                last_created = message.created.or(last_created);
+            }
+        }
+        usage.request_count = if replies > 0 { replies } else { metadata.message_count.unwrap_or(0) / 2 };
+        usage.estimated_cost = calculate_cost(usage.input_tokens, usage.output_tokens, None);
+
+        let timestamp = last_created
+            .and_then(|secs| Utc.timestamp_opt(secs, 0).single())
+            .or_else(|| fs::metadata(path).and_then(|m| m.modified()).ok().map(DateTime::<Utc>::from));
+
+        Some(Session {
+            working_dir: metadata.working_dir.filter(|d| !d.is_empty()),
+            usage,
+            timestamp,
+        })
+    }
+
+    fn walk_sessions(f: &mut dyn FnMut(Session)) -> Vec<PathBuf> {
+        let sessions_dirs: Vec<PathBuf> = Self::sessions_dirs().into_iter().filter(|p| p.exists()).collect();
+        for sessions_dir in &sessions_dirs {
+            for file in Self::session_files(sessions_dir) {
+                if let Some(session) = Self::read_session(&file) {
+                    f(session);
+                }
+            }
+        }
+        sessions_dirs
+    }
+
+    /// Usage of every session, pushing each one to `records` when given
+    fn scan(&self, mut records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut by_directory: HashMap<String, UsageStats> = HashMap::new();
+
+        let sessions_dirs = Self::walk_sessions(&mut |session| {
+            stats.add_at(&session.usage, session.timestamp, &ranges);
+            let directory = session.working_dir.clone().unwrap_or_else(|| "unknown".to_string());
+            by_directory.entry(directory).or_default().add_at(&session.usage, session.timestamp, &ranges);
+
+            if let (Some(records), Some(timestamp)) = (records.as_deref_mut(), session.timestamp) {
+                records.push(UsageRecord {
+                    provider: self.name(),
+                    timestamp,
+                    cwd: session.working_dir.map(PathBuf::from),
+                    git_branch: None,
+                    model: None,
+                    usage: session.usage,
+                });
+            }
+        });
+        if sessions_dirs.is_empty() {
+            return ProviderResult::not_found(self.name(), self.display_name());
+        }
+
+        let breakdown = UsageBreakdown::from_slices("directory", by_directory);
+
+        let note = match Self::configured_model() {
+            Some(model) => format!(
+                "Sessions don't record their model (Goose is now configured for {}); costs use default pricing",
+                model
+            ),
+            None => "Sessions don't record their model; costs use default pricing".to_string(),
+        };
+
+        ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &paths::join_paths(&sessions_dirs),
+        )
+        .with_breakdown(breakdown)
+        .with_note(note)
+    }
+}
+
+#[async_trait]
+impl Provider for GooseProvider {
+    fn name(&self) -> &'static str {
+        "goose"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Goose"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::sessions_dirs().iter().any(|p| p.exists())
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        Self::sessions_dirs()
+            .into_iter()
+            .chain(goose::config_file())
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        Ok(self.scan(None))
+    }
+
+    fn has_usage_records(&self) -> bool {
+        true
+    }
+
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
+
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        Ok((result, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn counts_replies_and_dates_sessions_by_the_last_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("20250130_1.jsonl");
+        fs::write(
+            &path,
+            "{\"working_dir\":\"/work/api\",\"input_tokens\":900,\"output_tokens\":100,\"accumulated_input_tokens\":1800,\"accumulated_output_tokens\":200,\"message_count\":8}\n\
+             {\"role\":\"user\",\"created\":1738227600}\n\
+             {\"role\":\"assistant\",\"created\":1738227610}\n\
+             {\"role\":\"user\",\"created\":1738227700}\n\
+             {\"role\":\"assistant\",\"created\":1738227720}\n",
+        )
+        .unwrap();
+
+        let session = GooseProvider::read_session(&path).unwrap();
+        assert_eq!(session.working_dir.as_deref(), Some("/work/api"));
+        assert_eq!((session.usage.input_tokens, session.usage.output_tokens), (1800, 200));
+        assert_eq!(session.usage.request_count, 2);
+        assert_eq!(session.timestamp, Utc.timestamp_opt(1_738_227_720, 0).single());
+        assert!(session.usage.estimated_cost > 0.0);
+    }
+
+    #[test]
+    fn total_only_sessions_fall_back_to_message_count_and_the_last_write() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("20250131_1.jsonl");
+        fs::write(&path, "{\"working_dir\":\"\",\"total_tokens\":5000,\"message_count\":6}\n").unwrap();
+        let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_738_500_000);
+        File::options().write(true).open(&path).unwrap().set_modified(modified).unwrap();
+
+        let session = GooseProvider::read_session(&path).unwrap();
+        assert_eq!(session.working_dir, None);
+        assert_eq!((session.usage.input_tokens, session.usage.output_tokens), (5000, 0));
+        assert_eq!(session.usage.request_count, 3);
+        assert_eq!(session.timestamp, Some(DateTime::<Utc>::from(modified)));
+
+        fs::write(&path, "{\"message_count\":2}\n").unwrap();
+        assert!(GooseProvider::read_session(&path).is_none());
+    }
+}
@@ -0,0 +1,276 @@
+//! LiteLLM Provider
+//! Reads gateway-side spend from a LiteLLM proxy's spend logs API (`/spend/logs/v2`).
+//! The proxy URL and key come from the config file (`[providers.litellm]`).
+
+use super::Provider;
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::format::format_number;
+use crate::utils::http;
+use crate::utils::time::{get_local_time_ranges, ranges_start};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// One page of spend log rows
+#[derive(Debug, Deserialize)]
+struct SpendLogsPage {
+    #[serde(default)]
+    data: Vec<SpendLog>,
+    total_pages: Option<u32>,
+}
+
+/// One request through the proxy
+#[derive(Debug, Deserialize)]
+struct SpendLog {
+    spend: Option<f64>,
+    prompt_tokens: Option<u64>,
+    completion_tokens: Option<u64>,
+    model: Option<String>,
+    /// Hashed virtual key
+    api_key: Option<String>,
+    user: Option<String>,
+    #[serde(rename = "startTime")]
+    start_time: Option<String>,
+    #[serde(default)]
+    request_tags: Vec<String>,
+    metadata: Option<SpendLogMetadata>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpendLogMetadata {
+    user_api_key_alias: Option<String>,
+}
+
+/// Rows per page requested from the proxy (the largest it allows)
+const PAGE_SIZE: u32 = 100;
+
+/// Most pages read per run, so a busy proxy isn't paged through without end
+const MAX_PAGES: u32 = 100;
+
+/// Spend log rows since the start of the report
+struct SpendLogs {
+    rows: Vec<SpendLog>,
+    /// Whether [`MAX_PAGES`] was reached before the last page
+    truncated: bool,
+}
+
+pub struct LiteLLMProvider;
+
+/// Per-model, per-key, per-user and per-tag slices
+#[derive(Default)]
+struct Breakdowns {
+    by_model: HashMap<String, UsageStats>,
+    by_api_key: HashMap<String, UsageStats>,
+    by_user: HashMap<String, UsageStats>,
+    by_tag: HashMap<String, UsageStats>,
+}
+
+impl LiteLLMProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Spend log timestamps are UTC, either RFC 3339 or without an offset
+    fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+        DateTime::parse_from_rfc3339(s)
+            .map(|dt| dt.with_timezone(&Utc))
+            .ok()
+            .or_else(|| NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f").ok().map(|dt| dt.and_utc()))
+            .or_else(|| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f").ok().map(|dt| dt.and_utc()))
+    }
+
+    /// Fetch the spend log rows since `start`, page by page, up to [`MAX_PAGES`]
+    async fn fetch_spend_logs(base_url: &str, api_key: &str, start: DateTime<Utc>) -> Result<SpendLogs, String> {
+        let start_date = start.format("%Y-%m-%d %H:%M:%S").to_string();
+        let end_date = Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut logs = Vec::new();
+        let mut page = 1;
+
+        loop {
+            let url = reqwest::Url::parse_with_params(
+                &format!("{}/spend/logs/v2", base_url),
+                &[
+                    ("start_date", start_date.as_str()),
+                    ("end_date", end_date.as_str()),
+                    ("page", &page.to_string()),
+                    ("page_size", &PAGE_SIZE.to_string()),
+                ],
+            )
+            .map_err(|_| format!("invalid base_url {:?}", base_url))?;
+
+            let response: SpendLogsPage = http::get_json(url.as_str(), api_key).await?;
+            let rows = response.data.len();
+            logs.extend(response.data);
+
+            let last_page = match response.total_pages {
+                Some(total) => page >= total,
+                None => rows < PAGE_SIZE as usize,
+            };
+            if last_page || rows == 0 {
+                return Ok(SpendLogs { rows: logs, truncated: false });
+            }
+            if page >= MAX_PAGES {
+                return Ok(SpendLogs { rows: logs, truncated: true });
+            }
+            page += 1;
+        }
+    }
+
+    fn process_log(
+        log: &SpendLog,
+        stats: &mut UsageStats,
+        breakdowns: &mut Breakdowns,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) {
+        let mut usage = UsageData::new();
+        usage.input_tokens = log.prompt_tokens.unwrap_or(0);
+        usage.output_tokens = log.completion_tokens.unwrap_or(0);
+        usage.request_count = 1;
+        usage.estimated_cost = log.spend.unwrap_or(0.0);
+
+        let timestamp = log.start_time.as_deref().and_then(Self::parse_timestamp);
+        stats.add_at(&usage, timestamp, ranges);
+
+        let model = log.model.clone().filter(|m| !m.is_empty()).unwrap_or_else(|| "unknown".to_string());
+        breakdowns.by_model.entry(model).or_default().add_at(&usage, timestamp, ranges);
+
+        // Key aliases are readable; otherwise show a prefix of the hashed key
+        let key = log
+            .metadata
+            .as_ref()
+            .and_then(|m| m.user_api_key_alias.clone())
+            .or_else(|| log.api_key.as_ref().map(|k| k.chars().take(12).collect()))
+            .filter(|k| !k.is_empty());
+        if let Some(key) = key {
+            breakdowns.by_api_key.entry(key).or_default().add_at(&usage, timestamp, ranges);
+        }
+
+        if let Some(user) = log.user.clone().filter(|u| !u.is_empty()) {
+            breakdowns.by_user.entry(user).or_default().add_at(&usage, timestamp, ranges);
+        }
+
+        // A request with several tags counts towards each of them
+        for tag in &log.request_tags {
+            breakdowns.by_tag.entry(tag.clone()).or_default().add_at(&usage, timestamp, ranges);
+        }
+    }
+}
+
+#[async_trait]
+impl Provider for LiteLLMProvider {
+    fn name(&self) -> &'static str {
+        "litellm"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "LiteLLM"
+    }
+
+    async fn is_available(&self) -> bool {
+        let config = config::get();
+        config.configured_base_url(self.name()).is_some() && config.api_key(self.name()).is_some()
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        vec![config::get()
+            .configured_base_url(self.name())
+            .unwrap_or_else(|| "providers.litellm.base_url (not configured)".to_string())]
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::Api
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let config = config::get();
+        let Some(base_url) = config.configured_base_url(self.name()) else {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        };
+        let Some(api_key) = config.api_key(self.name()) else {
+            return Ok(ProviderResult::no_key(self.name(), self.display_name()));
+        };
+
+        let ranges = Self::get_time_ranges();
+        let logs = match Self::fetch_spend_logs(&base_url, &api_key, ranges_start(&ranges)).await {
+            Ok(logs) => logs,
+            Err(e) => {
+                return Ok(ProviderResult::error(
+                    self.name(),
+                    self.display_name(),
+                    &format!("LiteLLM spend logs request failed: {}", e),
+                ))
+            }
+        };
+
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        for log in &logs.rows {
+            Self::process_log(log, &mut stats, &mut breakdowns, &ranges);
+        }
+
+        let mut breakdown = UsageBreakdown::from_slices("model", breakdowns.by_model);
+        breakdown.extend(UsageBreakdown::from_slices("api_key", breakdowns.by_api_key));
+        breakdown.extend(UsageBreakdown::from_slices("user", breakdowns.by_user));
+        breakdown.extend(UsageBreakdown::from_slices("tag", breakdowns.by_tag));
+
+        let mut result = ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &format!("LiteLLM ({})", base_url),
+        )
+        .with_breakdown(breakdown)
+        .with_note("Costs are the proxy's recorded spend");
+        if logs.truncated {
+            result = result.with_note(format!(
+                "Read the first {} spend log rows only ({} pages); usage beyond them is missing",
+                format_number(logs.rows.len() as u64),
+                MAX_PAGES
+            ));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::http::test_server;
+
+    fn page(rows: usize, total_pages: u32) -> String {
+        let row = serde_json::json!({
+            "spend": 0.01, "prompt_tokens": 100, "completion_tokens": 10, "model": "gpt-4o",
+            "startTime": Utc::now().format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+        });
+        serde_json::json!({ "data": vec![row; rows], "total_pages": total_pages }).to_string()
+    }
+
+    #[tokio::test]
+    async fn reads_every_page() {
+        let server = test_server::serve(vec![("page=2&", page(1, 2)), ("/spend/logs/v2", page(100, 2))]);
+
+        let logs = LiteLLMProvider::fetch_spend_logs(&server.url, "sk-1234", Utc::now()).await.unwrap();
+
+        assert_eq!(logs.rows.len(), 101);
+        assert!(!logs.truncated);
+        assert_eq!(server.requests().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn stops_after_the_page_limit() {
+        let server = test_server::serve(vec![("/spend/logs/v2", page(1, 10_000))]);
+
+        let logs = LiteLLMProvider::fetch_spend_logs(&server.url, "sk-1234", Utc::now()).await.unwrap();
+
+        assert!(logs.truncated);
+        assert_eq!(logs.rows.len(), MAX_PAGES as usize);
+        assert_eq!(server.requests().len(), MAX_PAGES as usize);
+    }
+}
@@ -0,0 +1,207 @@
+//! llm CLI Provider
+//! Reads the `responses` table of the `llm` CLI's log database (logs.db in its user directory,
+//! `$LLM_USER_PATH` or e.g. ~/.config/io.datasette.llm)
+//!
+//! Every prompt is one row with its model, UTC time and, in llm 0.19 and later, input and
+//! output token counts.
+
+use super::Provider;
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::db::with_db_snapshot;
+use crate::utils::paths::{self, llm};
+use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use rusqlite::Connection;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+pub struct LlmProvider;
+
+impl LlmProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Log databases, including any configured extras
+    fn logs_dbs() -> Vec<PathBuf> {
+        config::get().data_paths("llm", llm::logs_db())
+    }
+
+    /// `datetime_utc` is an ISO timestamp without an offset
+    fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%dT%H:%M:%S%.f")
+            .or_else(|_| NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S%.f"))
+            .ok()
+            .map(|dt| dt.and_utc())
+    }
+
+    /// Returns whether the table has token columns
+    fn process_database(
+        db_path: &Path,
+        stats: &mut UsageStats,
+        by_model: &mut HashMap<String, UsageStats>,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> Result<bool> {
+        with_db_snapshot(db_path, |conn| Self::process_responses(conn, stats, by_model, ranges))
+    }
+
+    fn process_responses(
+        conn: &Connection,
+        stats: &mut UsageStats,
+        by_model: &mut HashMap<String, UsageStats>,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> Result<bool> {
+        // Token columns were added in llm 0.19; older logs only count prompts
+        let has_tokens: bool = conn
+            .query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('responses') WHERE name = 'input_tokens'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap_or(false);
+        let query = if has_tokens {
+            "SELECT model, datetime_utc, input_tokens, output_tokens FROM responses"
+        } else {
+            "SELECT model, datetime_utc, NULL, NULL FROM responses"
+        };
+
+        let mut stmt = conn.prepare(query)?;
+        let rows = stmt.query_map([], |row| {
+            let model: Option<String> = row.get(0)?;
+            let datetime_utc: Option<String> = row.get(1)?;
+            let input_tokens: Option<i64> = row.get(2)?;
+            let output_tokens: Option<i64> = row.get(3)?;
+            Ok((model, datetime_utc, input_tokens, output_tokens))
+        })?;
+
+        for (model, datetime_utc, input_tokens, output_tokens) in rows.flatten() {
+            let mut usage = UsageData::new();
+            usage.input_tokens = input_tokens.unwrap_or(0).max(0) as u64;
+            usage.output_tokens = output_tokens.unwrap_or(0).max(0) as u64;
+            usage.request_count = 1;
+            usage.estimated_cost = calculate_cost(usage.input_tokens, usage.output_tokens, model.as_deref());
+
+            let timestamp = datetime_utc.as_deref().and_then(Self::parse_timestamp);
+            stats.add_at(&usage, timestamp, ranges);
+            let model = model.filter(|m| !m.is_empty()).unwrap_or_else(|| "unknown".to_string());
+            by_model.entry(model).or_default().add_at(&usage, timestamp, ranges);
+        }
+
+        Ok(has_tokens)
+    }
+}
+
+#[async_trait]
+impl Provider for LlmProvider {
+    fn name(&self) -> &'static str {
+        "llm"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "llm CLI"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::logs_dbs().iter().any(|p| p.exists())
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        Self::logs_dbs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::LocalDb
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let db_paths: Vec<PathBuf> = Self::logs_dbs().into_iter().filter(|p| p.exists()).collect();
+        if db_paths.is_empty() {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        }
+
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut by_model = HashMap::new();
+        let mut missing_tokens = false;
+
+        for db_path in &db_paths {
+            match Self::process_database(db_path, &mut stats, &mut by_model, &ranges) {
+                Ok(has_tokens) => missing_tokens |= !has_tokens,
+                Err(e) => return Ok(ProviderResult::error(self.name(), self.display_name(), &e.to_string())),
+            }
+        }
+
+        let mut result = ProviderResult::active(self.name(), self.display_name(), stats, &paths::join_paths(&db_paths))
+            .with_breakdown(UsageBreakdown::from_slices("model", by_model));
+        if missing_tokens {
+            result = result.with_note("Log database predates token logging (llm 0.19); only prompts are counted");
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn responses(with_tokens: bool) -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        let tokens = if with_tokens { ", input_tokens INTEGER, output_tokens INTEGER" } else { "" };
+        conn.execute_batch(&format!(
+            "CREATE TABLE responses (id TEXT PRIMARY KEY, model TEXT, prompt TEXT, response TEXT, datetime_utc TEXT{})",
+            tokens
+        ))
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn reads_responses_with_and_without_token_columns() {
+        let now = Utc::now().format("%Y-%m-%dT%H:%M:%S%.6f").to_string();
+        let ranges = get_local_time_ranges();
+
+        let conn = responses(true);
+        conn.execute("INSERT INTO responses VALUES ('1', 'gpt-4o-mini', 'hi', 'hello', ?1, 1000, 100)", [&now]).unwrap();
+        conn.execute("INSERT INTO responses VALUES ('2', 'gpt-4o-mini', 'hi', 'hello', '2024-01-01 09:30:00', 500, NULL)", [])
+            .unwrap();
+        conn.execute("INSERT INTO responses VALUES ('3', '', 'hi', 'hello', NULL, NULL, NULL)", []).unwrap();
+        let mut stats = UsageStats::default();
+        let mut by_model = HashMap::new();
+        assert!(LlmProvider::process_responses(&conn, &mut stats, &mut by_model, &ranges).unwrap());
+        assert_eq!((stats.total.input_tokens, stats.total.output_tokens, stats.total.request_count), (1500, 100, 3));
+        assert_eq!((stats.today.input_tokens, stats.today.request_count), (1000, 1));
+        assert_eq!(by_model["gpt-4o-mini"].total.request_count, 2);
+        assert_eq!(by_model["unknown"].total.request_count, 1);
+        assert!(stats.total.estimated_cost > 0.0);
+
+        let conn = responses(false);
+        conn.execute("INSERT INTO responses VALUES ('1', 'gpt-4o-mini', 'hi', 'hello', ?1)", [&now]).unwrap();
+        let mut stats = UsageStats::default();
+        let mut by_model = HashMap::new();
+        assert!(!LlmProvider::process_responses(&conn, &mut stats, &mut by_model, &ranges).unwrap());
+        assert_eq!((stats.today.request_count, stats.today.total_tokens()), (1, 0));
+    }
+
+    #[test]
+    fn timestamps_parse_with_a_t_or_a_space() {
+        let expected = Utc.with_ymd_and_hms(2024, 11, 5, 14, 3, 9).unwrap();
+        assert_eq!(LlmProvider::parse_timestamp("2024-11-05T14:03:09"), Some(expected));
+        assert_eq!(LlmProvider::parse_timestamp("2024-11-05 14:03:09"), Some(expected));
+        assert_eq!(
+            LlmProvider::parse_timestamp("2024-11-05T14:03:09.250000"),
+            Some(expected + chrono::Duration::milliseconds(250))
+        );
+    }
+}
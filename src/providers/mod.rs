@@ -7,7 +7,17 @@ mod cline;
 mod windsurf;
 mod opencode;
 mod openai_codex;
+mod codex_cli;
+mod anthropic_api;
+mod openrouter;
+mod deepseek;
+mod litellm;
 mod gemini_cli;
+mod aider;
+mod continue_dev;
+mod zed;
+mod goose;
+mod llm;
 mod amazon_q;
 mod tabnine;
 mod sourcegraph_cody;
@@ -21,14 +31,25 @@ pub use cline::ClineProvider;
 pub use windsurf::WindsurfProvider;
 pub use opencode::OpenCodeProvider;
 pub use openai_codex::OpenAICodexProvider;
+pub use codex_cli::CodexCliProvider;
+pub use anthropic_api::AnthropicApiProvider;
+pub use openrouter::OpenRouterProvider;
+pub use deepseek::DeepSeekProvider;
+pub use litellm::LiteLLMProvider;
 pub use gemini_cli::GeminiCLIProvider;
+pub use aider::AiderProvider;
+pub use continue_dev::ContinueProvider;
+pub use zed::ZedProvider;
+pub use goose::GooseProvider;
+pub use llm::LlmProvider;
 pub use amazon_q::AmazonQProvider;
 pub use tabnine::TabnineProvider;
 pub use sourcegraph_cody::SourcegraphCodyProvider;
 pub use replit::ReplitProvider;
 pub use warp::WarpProvider;
 
-use crate::types::{ProviderResult, TimeRange};
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageRecord};
+use crate::utils::config;
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -50,10 +71,41 @@ pub trait Provider: Send + Sync {
 
     /// Get paths to check for doctor command
     fn get_paths_to_check(&self) -> Vec<String>;
+
+    /// Where the provider's data comes from; only local sources are re-read under alternative home roots
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::LocalFile
+    }
+
+    /// Get individual usage records with working-directory attribution.
+    /// Providers that only know aggregate totals return nothing.
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        Ok(Vec::new())
+    }
+
+    /// Whether `get_usage_records` is implemented, i.e. usage can be attributed to repositories
+    fn has_usage_records(&self) -> bool {
+        false
+    }
+
+    /// Get usage statistics and the records behind them from a single scan of the data.
+    /// Providers without records return the statistics alone.
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        Ok((self.get_usage(None).await?, Vec::new()))
+    }
 }
 
-/// Get all available providers
+/// Get all providers enabled in the configuration
 pub fn get_all_providers() -> Vec<Box<dyn Provider>> {
+    let config = config::get();
+    builtin_providers()
+        .into_iter()
+        .filter(|p| config.is_enabled(p.name()))
+        .collect()
+}
+
+/// Every supported provider, regardless of configuration
+pub fn builtin_providers() -> Vec<Box<dyn Provider>> {
     vec![
         Box::new(ClaudeCodeProvider::new()),
         Box::new(CursorProvider::new()),
@@ -63,7 +115,17 @@ pub fn get_all_providers() -> Vec<Box<dyn Provider>> {
         Box::new(WarpProvider::new()),
         Box::new(OpenCodeProvider::new()),
         Box::new(OpenAICodexProvider::new()),
+        Box::new(CodexCliProvider::new()),
+        Box::new(AnthropicApiProvider::new()),
+        Box::new(OpenRouterProvider::new()),
+        Box::new(DeepSeekProvider::new()),
+        Box::new(LiteLLMProvider::new()),
         Box::new(GeminiCLIProvider::new()),
+        Box::new(AiderProvider::new()),
+        Box::new(ContinueProvider::new()),
+        Box::new(ZedProvider::new()),
+        Box::new(GooseProvider::new()),
+        Box::new(LlmProvider::new()),
         Box::new(AmazonQProvider::new()),
         Box::new(TabnineProvider::new()),
         Box::new(SourcegraphCodyProvider::new()),
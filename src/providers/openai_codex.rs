@@ -1,39 +1,68 @@
 //! OpenAI Codex Provider
-//! Uses OpenAI Usage API to fetch usage data
+//! Uses the OpenAI organization Usage and Costs APIs (`/v1/organization/usage/completions`,
+//! `/v1/organization/costs`) to fetch usage data. Requires an admin key.
+//!
+//! The Costs API bills every product (embeddings, images, fine-tuning, tools), while the
+//! usage counted here is completions only, so only cost line items of the completions
+//! models are applied.
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::time::get_local_time_ranges;
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::format::format_cost;
+use crate::utils::http::{self, Bucket, FetchError};
+use crate::utils::time::{get_local_time_ranges, ranges_start};
+use crate::utils::tokenizer::calculate_cost_with_cache;
 use anyhow::Result;
 use async_trait::async_trait;
-use chrono::{TimeZone, Utc};
-use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 
+/// `organization.usage.completions.result`; `input_tokens` includes `input_cached_tokens`
 #[derive(Debug, Deserialize)]
-struct OpenAIUsageResponse {
-    data: Option<Vec<UsageEntry>>,
+struct CompletionsResult {
+    input_tokens: Option<u64>,
+    input_cached_tokens: Option<u64>,
+    output_tokens: Option<u64>,
+    num_model_requests: Option<u64>,
+    model: Option<String>,
+    project_id: Option<String>,
+    api_key_id: Option<String>,
 }
 
+/// `organization.costs.result`; `line_item` is e.g. `gpt-4o-2024-08-06, input`
 #[derive(Debug, Deserialize)]
-struct UsageEntry {
-    aggregation_timestamp: Option<i64>,
-    n_requests: Option<u64>,
-    n_context_tokens_total: Option<u64>,
-    n_generated_tokens_total: Option<u64>,
+struct CostsResult {
+    amount: Option<CostAmount>,
+    line_item: Option<String>,
+    project_id: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct CostAmount {
+    value: Option<f64>,
+}
+
+/// OpenAI API base URL (overridable with `providers.openai-codex.base_url`)
+const DEFAULT_BASE_URL: &str = "https://api.openai.com";
+
+/// Largest page the API allows for daily buckets
+const PAGE_LIMIT: &str = "31";
+
 pub struct OpenAICodexProvider;
 
-/// Result of fetching usage data - includes error details for better reporting
-enum FetchResult {
-    Success(UsageStats),
-    Forbidden,
-    Unauthorized,
-    NotFound,
-    RateLimited,
-    NetworkError,
-    ParseError,
+/// Usage grouped the way it is reported
+#[derive(Default)]
+struct Report {
+    stats: UsageStats,
+    by_model: HashMap<String, UsageStats>,
+    by_project: HashMap<String, UsageStats>,
+    by_api_key: HashMap<String, UsageStats>,
+    /// Whether costs come from the Costs API rather than list prices
+    billed_costs: bool,
+    /// Billed costs of other products, left out of the totals
+    other_costs: f64,
 }
 
 impl OpenAICodexProvider {
@@ -42,75 +71,141 @@ impl OpenAICodexProvider {
     }
 
     fn get_api_key() -> Option<String> {
-        std::env::var("A2Z_OPENAI_KEY")
-            .or_else(|_| std::env::var("OPENAI_API_KEY"))
-            .or_else(|_| std::env::var("OPENAI_KEY"))
-            .ok()
+        ["A2Z_OPENAI_KEY", "OPENAI_API_KEY", "OPENAI_KEY"]
+            .into_iter()
+            .find_map(|name| std::env::var(name).ok().filter(|k| !k.is_empty()))
     }
 
     fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
         get_local_time_ranges()
     }
 
-    async fn fetch_usage_data(api_key: &str, ranges: &(TimeRange, TimeRange, TimeRange)) -> FetchResult {
-        let client = reqwest::Client::new();
-
-        let start_date = ranges.2.start.format("%Y-%m-%d").to_string();
-        let end_date = Utc::now().format("%Y-%m-%d").to_string();
-
-        let response = match client
-            .get(format!(
-                "https://api.openai.com/v1/organization/usage?start_date={}&end_date={}",
-                start_date, end_date
-            ))
-            .header("Authorization", format!("Bearer {}", api_key))
-            .header("Content-Type", "application/json")
-            .send()
-            .await
-        {
-            Ok(r) => r,
-            Err(_) => return FetchResult::NetworkError,
-        };
+    /// Fetch every bucket of a paginated report
+    async fn fetch_buckets<T: DeserializeOwned>(
+        base_url: &str,
+        api_key: &str,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> std::result::Result<Vec<Bucket<T>>, FetchError> {
+        let client = http::client();
+        http::fetch_buckets(|| {
+            client
+                .get(format!("{}{}", base_url, path))
+                .query(query)
+                .header("Authorization", format!("Bearer {}", api_key))
+                .header("Content-Type", "application/json")
+        })
+        .await
+    }
 
-        match response.status() {
-            StatusCode::OK => {},
-            StatusCode::FORBIDDEN => return FetchResult::Forbidden,
-            StatusCode::UNAUTHORIZED => return FetchResult::Unauthorized,
-            StatusCode::NOT_FOUND => return FetchResult::NotFound,
-            StatusCode::TOO_MANY_REQUESTS => return FetchResult::RateLimited,
-            _ => return FetchResult::NetworkError,
-        }
+    /// Whether a cost line item bills one of the completions `models` (lowercased)
+    fn is_completions_cost(line_item: &str, models: &HashSet<String>) -> bool {
+        let model = line_item.split_once(", ").map_or(line_item, |(model, _)| model);
+        models.contains(&model.to_lowercase())
+    }
 
-        let data: OpenAIUsageResponse = match response.json().await {
-            Ok(d) => d,
-            Err(_) => return FetchResult::ParseError,
+    async fn fetch_usage_data(
+        base_url: &str,
+        api_key: &str,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> std::result::Result<Report, FetchError> {
+        let start_time = ranges_start(ranges).timestamp().to_string();
+        let query = |group_by: &[&str]| -> Vec<(&str, String)> {
+            let mut query = vec![
+                ("start_time", start_time.clone()),
+                ("bucket_width", "1d".to_string()),
+                ("limit", PAGE_LIMIT.to_string()),
+            ];
+            query.extend(group_by.iter().map(|g| ("group_by", g.to_string())));
+            query
         };
-        let mut stats = UsageStats::default();
 
-        if let Some(entries) = data.data {
-            for entry in entries {
+        let usage_buckets = Self::fetch_buckets::<CompletionsResult>(
+            base_url,
+            api_key,
+            "/v1/organization/usage/completions",
+            &query(&["model", "project_id", "api_key_id"]),
+        )
+        .await?;
+
+        let mut report = Report::default();
+
+        for bucket in &usage_buckets {
+            let timestamp = Some(bucket.time());
+            for result in &bucket.results {
+                let cached = result.input_cached_tokens.unwrap_or(0);
                 let mut usage = UsageData::new();
-                usage.input_tokens = entry.n_context_tokens_total.unwrap_or(0);
-                usage.output_tokens = entry.n_generated_tokens_total.unwrap_or(0);
-                usage.request_count = entry.n_requests.unwrap_or(0);
-
-                stats.total.add(&usage);
-                stats.this_month.add(&usage);
-
-                if let Some(ts) = entry.aggregation_timestamp {
-                    if let Some(entry_date) = Utc.timestamp_opt(ts, 0).single() {
-                        if ranges.0.contains(entry_date) {
-                            stats.today.add(&usage);
-                        }
-                        if ranges.1.contains(entry_date) {
-                            stats.this_week.add(&usage);
-                        }
+                usage.input_tokens = result.input_tokens.unwrap_or(0).saturating_sub(cached);
+                usage.cache_read_tokens = cached;
+                usage.output_tokens = result.output_tokens.unwrap_or(0);
+                usage.request_count = result.num_model_requests.unwrap_or(0);
+                // List-price estimate; replaced by billed costs below where the API provides them
+                usage.estimated_cost = calculate_cost_with_cache(
+                    usage.input_tokens,
+                    usage.output_tokens,
+                    usage.cache_read_tokens,
+                    0,
+                    result.model.as_deref(),
+                );
+
+                report.stats.add_at(&usage, timestamp, ranges);
+                for (slices, key) in [
+                    (&mut report.by_model, &result.model),
+                    (&mut report.by_project, &result.project_id),
+                    (&mut report.by_api_key, &result.api_key_id),
+                ] {
+                    let label = key.clone().unwrap_or_else(|| "unknown".to_string());
+                    slices.entry(label).or_default().add_at(&usage, timestamp, ranges);
+                }
+            }
+        }
+
+        // Billed costs are only available per day, project and line item; without access
+        // to them the list-price estimates stay in place
+        if let Ok(cost_buckets) = Self::fetch_buckets::<CostsResult>(
+            base_url,
+            api_key,
+            "/v1/organization/costs",
+            &query(&["project_id", "line_item"]),
+        )
+        .await
+        {
+            let models: HashSet<String> = report.by_model.keys().map(|m| m.to_lowercase()).collect();
+            let mut costs = UsageStats::default();
+            let mut project_costs: HashMap<String, UsageStats> = HashMap::new();
+            let mut matched = false;
+
+            for bucket in &cost_buckets {
+                let timestamp = Some(bucket.time());
+                for result in &bucket.results {
+                    let amount = result.amount.as_ref().and_then(|a| a.value).unwrap_or(0.0);
+                    let line_item = result.line_item.as_deref().unwrap_or_default();
+                    if !Self::is_completions_cost(line_item, &models) {
+                        report.other_costs += amount;
+                        continue;
                     }
+                    matched = true;
+
+                    let mut usage = UsageData::new();
+                    usage.estimated_cost = amount;
+                    costs.add_at(&usage, timestamp, ranges);
+                    let project = result.project_id.clone().unwrap_or_else(|| "unknown".to_string());
+                    project_costs.entry(project).or_default().add_at(&usage, timestamp, ranges);
+                }
+            }
+
+            // Line items that name no completions model at all mean their format isn't
+            // understood, so the estimates are safer than zero
+            if matched || report.stats.total.total_tokens() == 0 {
+                report.stats.replace_costs(&costs);
+                for (project, stats) in report.by_project.iter_mut() {
+                    stats.replace_costs(&project_costs.remove(project).unwrap_or_default());
                 }
+                report.billed_costs = true;
             }
         }
 
-        FetchResult::Success(stats)
+        Ok(report)
     }
 }
 
@@ -132,6 +227,10 @@ impl Provider for OpenAICodexProvider {
         vec!["OPENAI_API_KEY environment variable".to_string()]
     }
 
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::Api
+    }
+
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
         let api_key = match Self::get_api_key() {
             Some(k) => k,
@@ -139,40 +238,61 @@ impl Provider for OpenAICodexProvider {
         };
 
         let ranges = Self::get_time_ranges();
+        let base_url = config::get().base_url(self.name(), DEFAULT_BASE_URL);
 
-        match Self::fetch_usage_data(&api_key, &ranges).await {
-            FetchResult::Success(stats) => Ok(ProviderResult::active(
-                self.name(),
-                self.display_name(),
-                stats,
-                "OpenAI API",
-            )),
-            FetchResult::Forbidden => Ok(ProviderResult::error(
+        match Self::fetch_usage_data(&base_url, &api_key, &ranges).await {
+            Ok(report) => {
+                let mut note = if report.billed_costs {
+                    "Costs are billed amounts for completions; model and API key slices use list-price estimates"
+                        .to_string()
+                } else {
+                    "Costs API unavailable; costs are list-price estimates".to_string()
+                };
+                if report.billed_costs && report.other_costs > 0.0 {
+                    note.push_str(&format!(
+                        "; {} billed for other products (embeddings, images, tools...) is not included",
+                        format_cost(report.other_costs)
+                    ));
+                }
+                let mut breakdown = UsageBreakdown::from_slices("model", report.by_model);
+                breakdown.extend(UsageBreakdown::from_slices("project", report.by_project));
+                breakdown.extend(UsageBreakdown::from_slices("api_key", report.by_api_key));
+
+                Ok(ProviderResult::active(
+                    self.name(),
+                    self.display_name(),
+                    report.stats,
+                    "OpenAI API",
+                )
+                .with_breakdown(breakdown)
+                .with_note(note))
+            }
+            Err(FetchError::Forbidden) => Ok(ProviderResult::error(
                 self.name(),
                 self.display_name(),
-                "API key lacks permission to access organization usage data (requires org admin or usage:read scope).",
+                "API key lacks permission to access organization usage data (requires an admin key).",
             )),
-            FetchResult::Unauthorized => Ok(ProviderResult::error(
+            Err(FetchError::Unauthorized) => Ok(ProviderResult::error(
                 self.name(),
                 self.display_name(),
                 "Invalid API key. Please check your OPENAI_API_KEY.",
             )),
-            FetchResult::NotFound => Ok(ProviderResult::error(
+            Err(FetchError::NotFound) => Ok(ProviderResult::error(
                 self.name(),
                 self.display_name(),
                 "Usage API endpoint not found. This may require an organization account.",
             )),
-            FetchResult::RateLimited => Ok(ProviderResult::error(
+            Err(FetchError::RateLimited) => Ok(ProviderResult::error(
                 self.name(),
                 self.display_name(),
                 "Rate limited by OpenAI API. Please try again later.",
             )),
-            FetchResult::NetworkError => Ok(ProviderResult::error(
+            Err(FetchError::Network) => Ok(ProviderResult::error(
                 self.name(),
                 self.display_name(),
                 "Network error connecting to OpenAI API.",
             )),
-            FetchResult::ParseError => Ok(ProviderResult::error(
+            Err(FetchError::Parse) => Ok(ProviderResult::error(
                 self.name(),
                 self.display_name(),
                 "Failed to parse OpenAI API response.",
@@ -180,3 +300,75 @@ impl Provider for OpenAICodexProvider {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::http::test_server;
+    use chrono::Utc;
+
+    #[tokio::test]
+    async fn only_completions_costs_replace_the_estimates() {
+        let now = Utc::now().timestamp();
+        let (start, end) = (now - now % 86_400, now - now % 86_400 + 86_400);
+        let usage = serde_json::json!({
+            "data": [{ "start_time": start, "end_time": end, "results": [{
+                "input_tokens": 1000, "input_cached_tokens": 200, "output_tokens": 100,
+                "num_model_requests": 2, "model": "gpt-4o-2024-08-06", "project_id": "proj_a", "api_key_id": "key_1",
+            }]}],
+            "has_more": false,
+        });
+        let cost = |value: f64, line_item: &str| {
+            serde_json::json!({ "amount": { "value": value, "currency": "usd" }, "line_item": line_item, "project_id": "proj_a" })
+        };
+        let costs = serde_json::json!({
+            "data": [{ "start_time": start, "end_time": end, "results": [
+                cost(0.25, "gpt-4o-2024-08-06, input"),
+                cost(0.5, "GPT-4o-2024-08-06, output"),
+                cost(2.0, "text-embedding-3-small"),
+                cost(1.0, "web search tool calls"),
+            ]}],
+            "has_more": false,
+        });
+        let server = test_server::serve(vec![
+            ("/v1/organization/usage/completions", usage.to_string()),
+            ("/v1/organization/costs", costs.to_string()),
+        ]);
+
+        let ranges = get_local_time_ranges();
+        let report = OpenAICodexProvider::fetch_usage_data(&server.url, "sk-admin", &ranges).await.unwrap();
+
+        assert!(report.billed_costs);
+        assert_eq!(report.stats.total.input_tokens, 800);
+        assert_eq!(report.stats.total.cache_read_tokens, 200);
+        assert_eq!(report.stats.total.estimated_cost, 0.75);
+        assert_eq!(report.stats.today.estimated_cost, 0.75);
+        assert_eq!(report.by_project["proj_a"].total.estimated_cost, 0.75);
+        assert_eq!(report.other_costs, 3.0);
+        assert!(server.requests().iter().any(|r| r.contains("group_by=line_item")));
+    }
+
+    #[tokio::test]
+    async fn unrecognized_line_items_keep_the_estimates() {
+        let now = Utc::now().timestamp();
+        let (start, end) = (now - now % 86_400, now - now % 86_400 + 86_400);
+        let usage = serde_json::json!({
+            "data": [{ "start_time": start, "end_time": end, "results": [{
+                "input_tokens": 1000, "output_tokens": 100, "num_model_requests": 1, "model": "gpt-4o",
+            }]}],
+        });
+        let costs = serde_json::json!({
+            "data": [{ "start_time": start, "end_time": end, "results": [{ "amount": { "value": 9.0 } }]}],
+        });
+        let server = test_server::serve(vec![
+            ("/v1/organization/usage/completions", usage.to_string()),
+            ("/v1/organization/costs", costs.to_string()),
+        ]);
+
+        let ranges = get_local_time_ranges();
+        let report = OpenAICodexProvider::fetch_usage_data(&server.url, "sk-admin", &ranges).await.unwrap();
+
+        assert!(!report.billed_costs);
+        assert_eq!(report.stats.total.estimated_cost, calculate_cost_with_cache(1000, 100, 0, 0, Some("gpt-4o")));
+    }
+}
@@ -1,18 +1,64 @@
 //! OpenCode Provider
-//! Reads session data from ~/.local/share/opencode/storage/message/
+//! Reads message data from ~/.local/share/opencode/storage/message/
+//!
+//! Current OpenCode stores one file per message (`<sessionID>/<msgID>.json`) with its own token
+//! counts, cost, provider, model and working directory. Older versions wrote one file per session
+//! at the top level.
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::opencode;
+use crate::types::{ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageRecord, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, opencode};
 use crate::utils::time::get_local_time_ranges;
-use crate::utils::tokenizer::calculate_cost;
+use crate::utils::tokenizer::{calculate_cost, calculate_cost_with_cache};
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+/// One message file in the current layout (`<sessionID>/<msgID>.json`)
+#[derive(Debug, Deserialize)]
+struct MessageFile {
+    role: Option<String>,
+    tokens: Option<MessageTokens>,
+    cost: Option<f64>,
+    #[serde(rename = "modelID")]
+    model_id: Option<String>,
+    #[serde(rename = "providerID")]
+    provider_id: Option<String>,
+    time: Option<MessageTime>,
+    path: Option<MessagePath>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagePath {
+    cwd: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageTokens {
+    input: Option<u64>,
+    output: Option<u64>,
+    reasoning: Option<u64>,
+    cache: Option<CacheTokens>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CacheTokens {
+    read: Option<u64>,
+    write: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageTime {
+    /// Milliseconds since the epoch
+    created: Option<i64>,
+}
+
+/// Legacy whole-session file
 #[derive(Debug, Deserialize)]
 struct OpenCodeSession {
     messages: Option<Vec<OpenCodeMessage>>,
@@ -44,6 +90,13 @@ struct MessageUsage {
     reasoning_tokens: Option<u64>,
 }
 
+/// Per-provider and per-model slices, for messages that name them
+#[derive(Default)]
+struct Breakdowns {
+    by_provider: HashMap<String, UsageStats>,
+    by_model: HashMap<String, UsageStats>,
+}
+
 pub struct OpenCodeProvider;
 
 impl OpenCodeProvider {
@@ -55,22 +108,112 @@ impl OpenCodeProvider {
         get_local_time_ranges()
     }
 
-    fn process_storage_dir(storage_dir: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
-        if let Ok(entries) = fs::read_dir(storage_dir) {
+    /// Message storage directories, including any configured extras
+    fn storage_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("opencode", opencode::storage_dir())
+    }
+
+    fn walk_session_dir(session_dir: &Path, f: &mut dyn FnMut(MessageFile)) {
+        if let Ok(entries) = fs::read_dir(session_dir) {
             for entry in entries.flatten() {
                 let path = entry.path();
                 if path.extension().map(|e| e == "json").unwrap_or(false) {
-                    Self::process_session_file(&path, stats, ranges);
+                    if let Ok(content) = fs::read_to_string(&path) {
+                        if let Ok(message) = serde_json::from_str::<MessageFile>(&content) {
+                            f(message);
+                        }
+                    }
                 }
             }
         }
     }
 
+    /// Read the messages and legacy session files of one storage directory, pushing each
+    /// message to `records` when given. Legacy session files don't record a directory, so
+    /// they only count towards the stats.
+    fn walk_storage_dir(
+        &self,
+        storage_dir: &Path,
+        stats: &mut UsageStats,
+        breakdowns: &mut Breakdowns,
+        mut records: Option<&mut Vec<UsageRecord>>,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) {
+        let Ok(entries) = fs::read_dir(storage_dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            if path.is_dir() {
+                Self::walk_session_dir(&path, &mut |message| {
+                    let Some(usage) = Self::message_usage(&message) else { return };
+                    let timestamp = Self::message_time(&message);
+
+                    stats.add_at(&usage, timestamp, ranges);
+                    if let Some(ref provider) = message.provider_id {
+                        breakdowns.by_provider.entry(provider.clone()).or_default().add_at(&usage, timestamp, ranges);
+                    }
+                    if let Some(ref model) = message.model_id {
+                        breakdowns.by_model.entry(model.clone()).or_default().add_at(&usage, timestamp, ranges);
+                    }
+
+                    if let (Some(records), Some(timestamp)) = (records.as_deref_mut(), timestamp) {
+                        records.push(UsageRecord {
+                            provider: self.name(),
+                            timestamp,
+                            cwd: message.path.and_then(|p| p.cwd).filter(|d| !d.is_empty()).map(PathBuf::from),
+                            git_branch: None,
+                            model: message.model_id,
+                            usage,
+                        });
+                    }
+                });
+            } else if path.extension().map(|e| e == "json").unwrap_or(false) {
+                Self::process_session_file(&path, stats, ranges);
+            }
+        }
+    }
+
+    /// Usage of an assistant message; other messages carry none
+    fn message_usage(message: &MessageFile) -> Option<UsageData> {
+        if message.role.as_deref().is_some_and(|r| r != "assistant") {
+            return None;
+        }
+        let tokens = message.tokens.as_ref()?;
+
+        let mut usage = UsageData::new();
+        usage.input_tokens = tokens.input.unwrap_or(0);
+        // Reasoning tokens are billed as output
+        usage.output_tokens = tokens.output.unwrap_or(0) + tokens.reasoning.unwrap_or(0);
+        usage.cache_read_tokens = tokens.cache.as_ref().and_then(|c| c.read).unwrap_or(0);
+        usage.cache_write_tokens = tokens.cache.as_ref().and_then(|c| c.write).unwrap_or(0);
+        usage.request_count = 1;
+        // OpenCode's own cost is authoritative (zero for subscription and free providers)
+        usage.estimated_cost = message.cost.unwrap_or_else(|| {
+            calculate_cost_with_cache(
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_read_tokens,
+                usage.cache_write_tokens,
+                message.model_id.as_deref(),
+            )
+        });
+
+        (usage.total_tokens() > 0).then_some(usage)
+    }
+
+    fn message_time(message: &MessageFile) -> Option<DateTime<Utc>> {
+        message
+            .time
+            .as_ref()
+            .and_then(|t| t.created)
+            .and_then(|ms| Utc.timestamp_millis_opt(ms).single())
+    }
+
     fn process_session_file(path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
         let file_mtime = fs::metadata(path)
             .and_then(|m| m.modified())
             .ok()
-            .map(|t| DateTime::<Utc>::from(t));
+            .map(DateTime::<Utc>::from);
 
         if let Ok(content) = fs::read_to_string(path) {
             if let Ok(session) = serde_json::from_str::<OpenCodeSession>(&content) {
@@ -98,6 +241,7 @@ impl OpenCodeProvider {
             usage.input_tokens = u.input_tokens.unwrap_or(0);
             usage.output_tokens = u.output_tokens.unwrap_or(0);
             usage.request_count = 1;
+            usage.estimated_cost = calculate_cost(usage.input_tokens, usage.output_tokens, None);
 
             Self::add_usage_to_stats(&usage, session_time, stats, ranges);
         }
@@ -129,6 +273,7 @@ impl OpenCodeProvider {
             if message.role.as_deref() == Some("assistant") {
                 usage.request_count = 1;
             }
+            usage.estimated_cost = calculate_cost(usage.input_tokens, usage.output_tokens, None);
 
             // Get message timestamp
             let msg_time = message.timestamp.as_ref()
@@ -165,19 +310,34 @@ impl OpenCodeProvider {
             return;
         }
 
-        stats.total.add(usage);
+        stats.add_at(usage, timestamp, ranges);
+    }
 
-        if let Some(ts) = timestamp {
-            if ranges.0.contains(ts) {
-                stats.today.add(usage);
-            }
-            if ranges.1.contains(ts) {
-                stats.this_week.add(usage);
-            }
-            if ranges.2.contains(ts) {
-                stats.this_month.add(usage);
-            }
+    /// Usage across all storage directories, pushing each message to `records` when given
+    fn scan(&self, mut records: Option<&mut Vec<UsageRecord>>) -> ProviderResult {
+        let storage_dirs: Vec<PathBuf> = Self::storage_dirs().into_iter().filter(|p| p.exists()).collect();
+        if storage_dirs.is_empty() {
+            return ProviderResult::not_found(self.name(), self.display_name());
         }
+
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+
+        for storage_dir in &storage_dirs {
+            self.walk_storage_dir(storage_dir, &mut stats, &mut breakdowns, records.as_deref_mut(), &ranges);
+        }
+
+        let mut breakdown = UsageBreakdown::from_slices("provider", breakdowns.by_provider);
+        breakdown.extend(UsageBreakdown::from_slices("model", breakdowns.by_model));
+
+        ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            stats,
+            &paths::join_paths(&storage_dirs),
+        )
+        .with_breakdown(breakdown)
     }
 }
 
@@ -192,37 +352,109 @@ impl Provider for OpenCodeProvider {
     }
 
     async fn is_available(&self) -> bool {
-        opencode::storage_dir().map(|p| p.exists()).unwrap_or(false)
+        Self::storage_dirs().iter().any(|p| p.exists())
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            opencode::storage_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        Self::storage_dirs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let storage_dir = match opencode::storage_dir() {
-            Some(p) if p.exists() => p,
-            _ => return Ok(ProviderResult::not_found(self.name(), self.display_name())),
+        Ok(self.scan(None))
+    }
+
+    fn has_usage_records(&self) -> bool {
+        true
+    }
+
+    async fn get_usage_records(&self) -> Result<Vec<UsageRecord>> {
+        let mut records = Vec::new();
+        self.scan(Some(&mut records));
+        Ok(records)
+    }
+
+    async fn get_usage_with_records(&self) -> Result<(ProviderResult, Vec<UsageRecord>)> {
+        let mut records = Vec::new();
+        let result = self.scan(Some(&mut records));
+        Ok((result, records))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_message_files_into_stats_breakdowns_and_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let session_dir = dir.path().join("ses_1");
+        fs::create_dir(&session_dir).unwrap();
+        let created = Utc::now().timestamp_millis();
+        let message = |role: &str, cost: Option<f64>| {
+            serde_json::json!({
+                "role": role,
+                "modelID": "claude-sonnet-4",
+                "providerID": "anthropic",
+                "cost": cost,
+                "time": { "created": created },
+                "path": { "cwd": "/work/api", "root": "/work" },
+                "tokens": { "input": 100, "output": 20, "reasoning": 5, "cache": { "read": 1000, "write": 0 } },
+            })
+            .to_string()
         };
+        fs::write(session_dir.join("msg_1.json"), message("user", None)).unwrap();
+        fs::write(session_dir.join("msg_2.json"), message("assistant", Some(0.0))).unwrap();
+        fs::write(session_dir.join("msg_3.json"), message("assistant", None)).unwrap();
 
-        let ranges = Self::get_time_ranges();
         let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        let mut records = Vec::new();
+        OpenCodeProvider::new().walk_storage_dir(dir.path(), &mut stats, &mut breakdowns, Some(&mut records), &get_local_time_ranges());
+
+        assert_eq!(stats.today.request_count, 2);
+        assert_eq!((stats.total.input_tokens, stats.total.output_tokens, stats.total.cache_read_tokens), (200, 50, 2000));
+        // A reported cost of 0 is kept; a missing one is estimated
+        let estimated = calculate_cost_with_cache(100, 25, 1000, 0, Some("claude-sonnet-4"));
+        assert!(estimated > 0.0);
+        assert_eq!(stats.total.estimated_cost, estimated);
+        assert_eq!(breakdowns.by_provider["anthropic"].total.request_count, 2);
+        assert_eq!(breakdowns.by_model["claude-sonnet-4"].total.request_count, 2);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].cwd.as_deref(), Some(Path::new("/work/api")));
+        assert_eq!(records[0].model.as_deref(), Some("claude-sonnet-4"));
+    }
 
-        Self::process_storage_dir(&storage_dir, &mut stats, &ranges);
-
-        // Calculate costs
-        stats.today.estimated_cost = calculate_cost(stats.today.input_tokens, stats.today.output_tokens, None);
-        stats.this_week.estimated_cost = calculate_cost(stats.this_week.input_tokens, stats.this_week.output_tokens, None);
-        stats.this_month.estimated_cost = calculate_cost(stats.this_month.input_tokens, stats.this_month.output_tokens, None);
-        stats.total.estimated_cost = calculate_cost(stats.total.input_tokens, stats.total.output_tokens, None);
+    #[test]
+    fn reads_legacy_session_files_without_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Utc::now();
+        fs::write(
+            dir.path().join("session.json"),
+            serde_json::json!({
+                "created_at": "2024-06-01T10:00:00Z",
+                "messages": [
+                    { "role": "user", "usage": { "input_tokens": 50, "output_tokens": 0 }, "timestamp": now.timestamp() },
+                    { "role": "assistant", "usage": { "input_tokens": 0, "output_tokens": 40, "reasoning_tokens": 10 }, "timestamp": now.to_rfc3339() },
+                    { "role": "assistant", "usage": { "input_tokens": 30, "output_tokens": 5 } },
+                ],
+            })
+            .to_string(),
+        )
+        .unwrap();
 
-        Ok(ProviderResult::active(
-            self.name(),
-            self.display_name(),
-            stats,
-            &storage_dir.to_string_lossy(),
-        ))
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        let mut records = Vec::new();
+        OpenCodeProvider::new().walk_storage_dir(dir.path(), &mut stats, &mut breakdowns, Some(&mut records), &get_local_time_ranges());
+
+        assert_eq!((stats.total.input_tokens, stats.total.output_tokens), (80, 55));
+        assert_eq!(stats.total.request_count, 2);
+        // The last message falls back to the session's creation time
+        assert_eq!((stats.today.input_tokens, stats.today.output_tokens), (50, 50));
+        assert!(breakdowns.by_model.is_empty());
+        assert!(records.is_empty());
     }
 }
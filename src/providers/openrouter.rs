@@ -0,0 +1,191 @@
+//! OpenRouter Provider
+//! Uses the OpenRouter key (`/api/v1/key`) and credits (`/api/v1/credits`) endpoints
+//! to fetch the API key's spend and the account's remaining prepaid credit
+
+use super::Provider;
+use crate::types::{Balance, DataSourceType, ProviderResult, TimeRange, UsageStats};
+use crate::utils::config::{self, DEFAULT_LOW_BALANCE};
+use crate::utils::http;
+use anyhow::Result;
+use async_trait::async_trait;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct DataResponse<T> {
+    data: T,
+}
+
+/// `/api/v1/key`: spend of the key making the request, in USD
+#[derive(Debug, Deserialize)]
+struct KeyInfo {
+    usage: Option<f64>,
+    usage_daily: Option<f64>,
+    usage_weekly: Option<f64>,
+    usage_monthly: Option<f64>,
+    limit: Option<f64>,
+    limit_remaining: Option<f64>,
+}
+
+/// `/api/v1/credits`: account-wide credit, in USD
+#[derive(Debug, Deserialize)]
+struct Credits {
+    total_credits: Option<f64>,
+    total_usage: Option<f64>,
+}
+
+/// OpenRouter base URL (overridable with `providers.openrouter.base_url`)
+const DEFAULT_BASE_URL: &str = "https://openrouter.ai";
+
+pub struct OpenRouterProvider;
+
+impl OpenRouterProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_api_key() -> Option<String> {
+        std::env::var("OPENROUTER_API_KEY").ok().filter(|k| !k.is_empty())
+    }
+
+    fn stats_from_key(key: &KeyInfo) -> UsageStats {
+        let mut stats = UsageStats::default();
+        for (period, spend) in stats.periods_mut().into_iter().zip([
+            key.usage_daily,
+            key.usage_weekly,
+            key.usage_monthly,
+            key.usage,
+        ]) {
+            period.estimated_cost = spend.unwrap_or(0.0);
+        }
+        stats
+    }
+
+    /// Account credit when the key may read it, otherwise the key's own spending limit.
+    /// Credits without both totals are treated as unreadable rather than unspent.
+    fn balance(credits: Option<&Credits>, key: &KeyInfo, threshold: f64) -> Option<Balance> {
+        let (remaining, total) = match credits {
+            Some(Credits { total_credits: Some(total), total_usage: Some(used) }) => (total - used, Some(*total)),
+            _ => (key.limit_remaining?, key.limit),
+        };
+        Some(Balance {
+            currency: "USD".to_string(),
+            remaining,
+            total,
+            low: remaining < threshold,
+        })
+    }
+
+    /// Key spend and balance from the API at `base_url`
+    async fn fetch(&self, base_url: &str, api_key: &str, threshold: f64) -> ProviderResult {
+        let key = match http::get_json::<DataResponse<KeyInfo>>(&format!("{}/api/v1/key", base_url), api_key).await {
+            Ok(r) => r.data,
+            Err(e) => {
+                return ProviderResult::error(
+                    self.name(),
+                    self.display_name(),
+                    &format!("OpenRouter key lookup failed: {}", e),
+                )
+            }
+        };
+        // Reading account credit needs a key with access to it; fall back to the key's limit
+        let credits = http::get_json::<DataResponse<Credits>>(&format!("{}/api/v1/credits", base_url), api_key)
+            .await
+            .ok()
+            .map(|r| r.data);
+
+        let mut result = ProviderResult::active(
+            self.name(),
+            self.display_name(),
+            Self::stats_from_key(&key),
+            "OpenRouter API",
+        )
+        .with_note("Spend is per API key in USD, over OpenRouter's UTC day/week/month");
+
+        match Self::balance(credits.as_ref(), &key, threshold) {
+            Some(balance) => result = result.with_balance(balance),
+            None => result = result.with_note("Remaining credit unavailable (key has no limit and cannot read account credits)"),
+        }
+        result
+    }
+}
+
+#[async_trait]
+impl Provider for OpenRouterProvider {
+    fn name(&self) -> &'static str {
+        "openrouter"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "OpenRouter"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::get_api_key().is_some()
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        vec!["OPENROUTER_API_KEY environment variable".to_string()]
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::Api
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let api_key = match Self::get_api_key() {
+            Some(k) => k,
+            None => return Ok(ProviderResult::no_key(self.name(), self.display_name())),
+        };
+
+        let config = config::get();
+        let threshold = config.low_balance(self.name()).unwrap_or(DEFAULT_LOW_BALANCE);
+        Ok(self.fetch(&config.base_url(self.name(), DEFAULT_BASE_URL), &api_key, threshold).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::http::test_server;
+
+    fn key(limit: Option<f64>, limit_remaining: Option<f64>) -> String {
+        serde_json::json!({ "data": { "usage": 12.5, "usage_daily": 0.5, "limit": limit, "limit_remaining": limit_remaining } })
+            .to_string()
+    }
+
+    #[tokio::test]
+    async fn account_credit_is_the_balance() {
+        let server = test_server::serve(vec![
+            ("/api/v1/key", key(None, None)),
+            ("/api/v1/credits", r#"{"data":{"total_credits":50.0,"total_usage":47.0}}"#.to_string()),
+        ]);
+
+        let result = OpenRouterProvider::new().fetch(&server.url, "sk-or-x", DEFAULT_LOW_BALANCE).await;
+
+        let usage = result.usage.unwrap();
+        assert_eq!((usage.today.estimated_cost, usage.total.estimated_cost), (0.5, 12.5));
+        let balance = result.balance.unwrap();
+        assert_eq!((balance.remaining, balance.total), (3.0, Some(50.0)));
+        assert!(balance.low);
+    }
+
+    #[tokio::test]
+    async fn credits_without_usage_fall_back_to_the_key_limit() {
+        let server = test_server::serve(vec![
+            ("/api/v1/key", key(Some(20.0), Some(8.0))),
+            ("/api/v1/credits", r#"{"data":{"total_credits":50.0}}"#.to_string()),
+        ]);
+        let result = OpenRouterProvider::new().fetch(&server.url, "sk-or-x", DEFAULT_LOW_BALANCE).await;
+        let balance = result.balance.unwrap();
+        assert_eq!((balance.remaining, balance.total), (8.0, Some(20.0)));
+        assert!(!balance.low);
+
+        // Without a key limit either, the balance is unknown rather than the full credit
+        let server = test_server::serve(vec![
+            ("/api/v1/key", key(None, None)),
+            ("/api/v1/credits", r#"{"data":{"total_credits":50.0}}"#.to_string()),
+        ]);
+        let result = OpenRouterProvider::new().fetch(&server.url, "sk-or-x", DEFAULT_LOW_BALANCE).await;
+        assert!(result.balance.is_none());
+    }
+}
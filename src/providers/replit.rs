@@ -2,7 +2,7 @@
 //! Implemented as "Link Only" mode since it requires web authentication
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange};
+use crate::types::{DataSourceType, ProviderResult, TimeRange};
 use anyhow::Result;
 use async_trait::async_trait;
 
@@ -34,6 +34,10 @@ impl Provider for ReplitProvider {
         vec!["https://replit.com/usage (web only)".to_string()]
     }
 
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::LinkOnly
+    }
+
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
         // Replit is web-only and requires session cookie authentication
         // We provide a link to the usage page instead
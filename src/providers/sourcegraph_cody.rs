@@ -2,15 +2,17 @@
 //! Reads from VS Code extension storage
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::sourcegraph_cody;
+use crate::types::{ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::paths::{self, sourcegraph_cody, vscode};
 use crate::utils::time::get_local_time_ranges;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct CodyStateData {
@@ -42,6 +44,11 @@ impl SourcegraphCodyProvider {
         get_local_time_ranges()
     }
 
+    /// Extension storage directories in every VS Code family editor, plus configured extras
+    fn extension_dirs() -> Vec<(&'static str, PathBuf)> {
+        config::get().labeled_data_paths("sourcegraph-cody", sourcegraph_cody::vscode_extensions())
+    }
+
     fn process_extension_dir(dir: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
         if let Ok(entries) = fs::read_dir(dir) {
             for entry in entries.flatten() {
@@ -57,7 +64,7 @@ impl SourcegraphCodyProvider {
         let file_mtime = fs::metadata(path)
             .and_then(|m| m.modified())
             .ok()
-            .map(|t| DateTime::<Utc>::from(t));
+            .map(DateTime::<Utc>::from);
 
         if let Ok(content) = fs::read_to_string(path) {
             if let Ok(data) = serde_json::from_str::<CodyStateData>(&content) {
@@ -133,31 +140,49 @@ impl Provider for SourcegraphCodyProvider {
     }
 
     async fn is_available(&self) -> bool {
-        sourcegraph_cody::vscode_extension().map(|p| p.exists()).unwrap_or(false)
+        Self::extension_dirs().iter().any(|(_, p)| p.exists())
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            sourcegraph_cody::vscode_extension().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        vscode::installed(Self::extension_dirs())
+            .iter()
+            .map(|(_, p)| p.to_string_lossy().to_string())
+            .collect()
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let extension_dir = match sourcegraph_cody::vscode_extension() {
-            Some(p) if p.exists() => p,
-            _ => return Ok(ProviderResult::not_found(self.name(), self.display_name())),
-        };
+        let extension_dirs: Vec<(&'static str, PathBuf)> = Self::extension_dirs()
+            .into_iter()
+            .filter(|(_, p)| p.exists())
+            .collect();
+        if extension_dirs.is_empty() {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        }
 
         let ranges = Self::get_time_ranges();
         let mut stats = UsageStats::default();
+        let mut by_editor: HashMap<&'static str, UsageStats> = HashMap::new();
+
+        for (editor, extension_dir) in &extension_dirs {
+            let mut dir_stats = UsageStats::default();
+            Self::process_extension_dir(extension_dir, &mut dir_stats, &ranges);
+            stats.merge(&dir_stats);
+            by_editor.entry(editor).or_default().merge(&dir_stats);
+        }
 
-        Self::process_extension_dir(&extension_dir, &mut stats, &ranges);
+        let mut breakdown: Vec<UsageBreakdown> = by_editor
+            .into_iter()
+            .map(|(editor, usage)| UsageBreakdown { dimension: "editor".to_string(), label: editor.to_string(), usage })
+            .collect();
+        breakdown.sort_by_key(|b| std::cmp::Reverse(b.usage.total.request_count));
+        let extension_dirs: Vec<PathBuf> = extension_dirs.into_iter().map(|(_, p)| p).collect();
 
         Ok(ProviderResult::active(
             self.name(),
             self.display_name(),
             stats,
-            &extension_dir.to_string_lossy(),
-        ))
+            &paths::join_paths(&extension_dirs),
+        )
+        .with_breakdown(breakdown))
     }
 }
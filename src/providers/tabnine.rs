@@ -3,14 +3,15 @@
 
 use super::Provider;
 use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::tabnine;
+use crate::utils::config;
+use crate::utils::paths::{self, tabnine};
 use crate::utils::time::get_local_time_ranges;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct TabnineLogEntry {
@@ -24,6 +25,7 @@ struct TabnineLogEntry {
 
 #[derive(Debug, Deserialize)]
 struct TabnineMeta {
+    #[allow(dead_code)]
     net_length: Option<u64>,
     tokens_used: Option<u64>,
 }
@@ -31,6 +33,7 @@ struct TabnineMeta {
 #[derive(Debug, Deserialize)]
 struct TabnineUsage {
     tokens: Option<u64>,
+    #[allow(dead_code)]
     chars: Option<u64>,
 }
 
@@ -45,6 +48,11 @@ impl TabnineProvider {
         get_local_time_ranges()
     }
 
+    /// Log directories, including any configured extras
+    fn logs_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("tabnine", tabnine::logs_dir())
+    }
+
     fn process_logs_dir(logs_dir: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
         if let Ok(entries) = fs::read_dir(logs_dir) {
             for entry in entries.flatten() {
@@ -60,7 +68,7 @@ impl TabnineProvider {
         let file_mtime = fs::metadata(path)
             .and_then(|m| m.modified())
             .ok()
-            .map(|t| DateTime::<Utc>::from(t));
+            .map(DateTime::<Utc>::from);
 
         if let Ok(content) = fs::read_to_string(path) {
             for line in content.lines() {
@@ -154,31 +162,34 @@ impl Provider for TabnineProvider {
     }
 
     async fn is_available(&self) -> bool {
-        tabnine::logs_dir().map(|p| p.exists()).unwrap_or(false)
+        Self::logs_dirs().iter().any(|p| p.exists())
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            tabnine::logs_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        Self::logs_dirs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let logs_dir = match tabnine::logs_dir() {
-            Some(p) if p.exists() => p,
-            _ => return Ok(ProviderResult::not_found(self.name(), self.display_name())),
-        };
+        let logs_dirs: Vec<PathBuf> = Self::logs_dirs().into_iter().filter(|p| p.exists()).collect();
+        if logs_dirs.is_empty() {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        }
 
         let ranges = Self::get_time_ranges();
         let mut stats = UsageStats::default();
 
-        Self::process_logs_dir(&logs_dir, &mut stats, &ranges);
+        for logs_dir in &logs_dirs {
+            Self::process_logs_dir(logs_dir, &mut stats, &ranges);
+        }
 
         Ok(ProviderResult::active(
             self.name(),
             self.display_name(),
             stats,
-            &logs_dir.to_string_lossy(),
+            &paths::join_paths(&logs_dirs),
         ))
     }
 }
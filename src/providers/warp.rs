@@ -2,16 +2,17 @@
 //! Reads AI usage data from Warp's SQLite database
 
 use super::Provider;
-use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageData, UsageStats};
 use crate::utils::db::with_db_snapshot;
-use crate::utils::paths::warp;
+use crate::utils::config;
+use crate::utils::paths::{self, warp};
 use crate::utils::time::get_local_time_ranges;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use rusqlite::Connection;
 use serde::Deserialize;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct ConversationData {
@@ -45,15 +46,18 @@ impl WarpProvider {
         get_local_time_ranges()
     }
 
-    fn process_database(db_path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) -> Result<()> {
-        with_db_snapshot(db_path, |snapshot_path| {
-            let conn = Connection::open(snapshot_path)?;
+    /// Warp databases, including any configured extras
+    fn sqlite_dbs() -> Vec<PathBuf> {
+        config::get().data_paths("warp", warp::sqlite_db())
+    }
 
+    fn process_database(db_path: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) -> Result<()> {
+        with_db_snapshot(db_path, |conn| {
             // First, try to get actual token usage from agent_conversations table
-            Self::process_agent_conversations(&conn, stats, ranges)?;
+            Self::process_agent_conversations(conn, stats, ranges)?;
 
             // Also count AI queries for request count
-            Self::process_ai_queries(&conn, stats, ranges)?;
+            Self::process_ai_queries(conn, stats, ranges)?;
 
             Ok(())
         })?;
@@ -217,32 +221,40 @@ impl Provider for WarpProvider {
     }
 
     async fn is_available(&self) -> bool {
-        warp::sqlite_db().map(|p| p.exists()).unwrap_or(false)
+        Self::sqlite_dbs().iter().any(|p| p.exists())
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            warp::sqlite_db().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            warp::logs_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        let mut paths: Vec<String> = Self::sqlite_dbs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.push(warp::logs_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::LocalDb
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let db_path = match warp::sqlite_db() {
-            Some(p) if p.exists() => p,
-            _ => return Ok(ProviderResult::not_found(self.name(), self.display_name())),
-        };
+        let db_paths: Vec<PathBuf> = Self::sqlite_dbs().into_iter().filter(|p| p.exists()).collect();
+        if db_paths.is_empty() {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        }
 
         let ranges = Self::get_time_ranges();
         let mut stats = UsageStats::default();
 
-        if let Err(e) = Self::process_database(&db_path, &mut stats, &ranges) {
-            return Ok(ProviderResult::error(self.name(), self.display_name(), &e.to_string()));
+        for db_path in &db_paths {
+            if let Err(e) = Self::process_database(db_path, &mut stats, &ranges) {
+                return Ok(ProviderResult::error(self.name(), self.display_name(), &e.to_string()));
+            }
         }
 
         let data_source = format!(
             "{} (total tokens only; no input/output breakdown)",
-            db_path.to_string_lossy()
+            paths::join_paths(&db_paths)
         );
 
         Ok(ProviderResult::active(
@@ -3,14 +3,15 @@
 
 use super::Provider;
 use crate::types::{ProviderResult, TimeRange, UsageData, UsageStats};
-use crate::utils::paths::windsurf;
+use crate::utils::config;
+use crate::utils::paths::{self, windsurf};
 use crate::utils::time::get_local_time_ranges;
 use anyhow::Result;
 use async_trait::async_trait;
 use chrono::{DateTime, TimeZone, Utc};
 use serde::Deserialize;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Deserialize)]
 struct CascadeLogEntry {
@@ -41,6 +42,11 @@ impl WindsurfProvider {
         get_local_time_ranges()
     }
 
+    /// Cascade log directories, including any configured extras
+    fn cascade_dirs() -> Vec<PathBuf> {
+        config::get().data_paths("windsurf", windsurf::cascade_dir())
+    }
+
     fn process_cascade_dir(cascade_dir: &Path, stats: &mut UsageStats, ranges: &(TimeRange, TimeRange, TimeRange)) {
         if let Ok(entries) = fs::read_dir(cascade_dir) {
             for entry in entries.flatten() {
@@ -210,24 +216,26 @@ impl Provider for WindsurfProvider {
     }
 
     async fn is_available(&self) -> bool {
-        windsurf::cascade_dir().map(|p| p.exists()).unwrap_or(false)
+        Self::cascade_dirs().iter().any(|p| p.exists())
             || windsurf::memories_dir().map(|p| p.exists()).unwrap_or(false)
             || windsurf::config_dir().map(|p| p.exists()).unwrap_or(false)
     }
 
     fn get_paths_to_check(&self) -> Vec<String> {
-        vec![
-            windsurf::cascade_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            windsurf::memories_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-            windsurf::config_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
-        ]
+        let mut paths: Vec<String> = Self::cascade_dirs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        paths.push(windsurf::memories_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths.push(windsurf::config_dir().map(|p| p.to_string_lossy().to_string()).unwrap_or_default());
+        paths
     }
 
     async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
-        let cascade_dir = windsurf::cascade_dir();
+        let cascade_dirs: Vec<PathBuf> = Self::cascade_dirs().into_iter().filter(|p| p.exists()).collect();
         let memories_dir = windsurf::memories_dir();
 
-        let has_cascade = cascade_dir.as_ref().map(|p| p.exists()).unwrap_or(false);
+        let has_cascade = !cascade_dirs.is_empty();
         let has_memories = memories_dir.as_ref().map(|p| p.exists()).unwrap_or(false);
 
         if !has_cascade && !has_memories {
@@ -247,38 +255,38 @@ impl Provider for WindsurfProvider {
         let mut stats = UsageStats::default();
 
         let mut has_pb_only = false;
-        if let Some(ref dir) = cascade_dir {
-            if dir.exists() {
-                has_pb_only = Self::cascade_has_pb_logs(dir);
-                Self::process_cascade_dir(dir, &mut stats, &ranges);
-            }
+        for dir in &cascade_dirs {
+            has_pb_only |= Self::cascade_has_pb_logs(dir);
+            Self::process_cascade_dir(dir, &mut stats, &ranges);
         }
 
         // Note: Windsurf "memories" are not a reliable source of usage/token counts.
         // We do not estimate token usage from these files.
         let _ = memories_dir; // keep for availability checks
 
-        let data_source = cascade_dir
-            .as_ref()
-            .map(|p| p.to_string_lossy().to_string())
-            .unwrap_or_else(|| "Windsurf".to_string());
+        let data_source = if cascade_dirs.is_empty() {
+            "Windsurf".to_string()
+        } else {
+            paths::join_paths(&cascade_dirs)
+        };
 
         // If we didn't parse any real token data but we do see protobuf logs,
         // count the number of sessions as the best available metric.
         // The .pb files are encrypted (not just protobuf-encoded), so we cannot read token counts.
         if stats.total.input_tokens == 0 && stats.total.output_tokens == 0 && stats.total.request_count == 0 && has_pb_only {
             // Count sessions from encrypted .pb files with time periods
-            if let Some(ref dir) = cascade_dir {
-                let session_stats = Self::count_pb_files_by_time(dir, &ranges);
-                
-                if session_stats.total.request_count > 0 {
-                    return Ok(ProviderResult::active(
-                        self.name(),
-                        self.display_name(),
-                        session_stats,
-                        "Session count only (token data encrypted). See windsurf.ai for details.",
-                    ));
-                }
+            let mut session_stats = UsageStats::default();
+            for dir in &cascade_dirs {
+                session_stats.merge(&Self::count_pb_files_by_time(dir, &ranges));
+            }
+
+            if session_stats.total.request_count > 0 {
+                return Ok(ProviderResult::active(
+                    self.name(),
+                    self.display_name(),
+                    session_stats,
+                    "Session count only (token data encrypted). See windsurf.ai for details.",
+                ));
             }
             
             return Ok(ProviderResult::unsupported(
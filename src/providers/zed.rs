@@ -0,0 +1,311 @@
+//! Zed Provider
+//! Reads agent panel threads from Zed's threads database (~/.local/share/zed/threads/threads.db)
+//!
+//! Each row of the `threads` table holds one serialized thread with its model and cumulative
+//! token usage, as JSON or (in newer versions) zstd-compressed JSON (`data_type = 'zstd'`).
+
+use super::Provider;
+use crate::types::{DataSourceType, ProviderResult, TimeRange, UsageBreakdown, UsageData, UsageStats};
+use crate::utils::config;
+use crate::utils::db::with_db_snapshot;
+use crate::utils::paths::{self, zed};
+use crate::utils::time::get_local_time_ranges;
+use crate::utils::tokenizer::calculate_cost_with_cache;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use rusqlite::Connection;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// The parts of a serialized thread that carry usage
+#[derive(Debug, Deserialize)]
+struct SerializedThread {
+    model: Option<SerializedModel>,
+    cumulative_token_usage: Option<TokenUsage>,
+    /// Usage per request: a list in older threads, keyed by message id in newer ones
+    request_token_usage: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SerializedModel {
+    provider: Option<String>,
+    model: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+}
+
+/// Threads listed individually in the breakdown; the rest are grouped
+const TOP_THREADS: usize = 10;
+
+pub struct ZedProvider;
+
+/// Per-model and per-thread slices
+#[derive(Default)]
+struct Breakdowns {
+    by_model: HashMap<String, UsageStats>,
+    /// Keyed by thread id, since summaries repeat; holds the summary to display
+    by_thread: HashMap<String, (String, UsageStats)>,
+}
+
+impl ZedProvider {
+    pub fn new() -> Self {
+        Self
+    }
+
+    fn get_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
+        get_local_time_ranges()
+    }
+
+    /// Threads databases, including any configured extras
+    fn threads_dbs() -> Vec<PathBuf> {
+        config::get().data_paths("zed", zed::threads_db())
+    }
+
+    /// Reads every thread row; returns how many could not be decoded
+    fn process_database(
+        db_path: &Path,
+        stats: &mut UsageStats,
+        breakdowns: &mut Breakdowns,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> Result<usize> {
+        with_db_snapshot(db_path, |conn| Self::process_threads(conn, stats, breakdowns, ranges))
+    }
+
+    fn process_threads(
+        conn: &Connection,
+        stats: &mut UsageStats,
+        breakdowns: &mut Breakdowns,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) -> Result<usize> {
+        let mut stmt = conn.prepare("SELECT id, summary, updated_at, data_type, data FROM threads")?;
+        let rows = stmt.query_map([], |row| {
+            let id: String = row.get(0)?;
+            let summary: Option<String> = row.get(1)?;
+            let updated_at: Option<String> = row.get(2)?;
+            let data_type: Option<String> = row.get(3)?;
+            // Zed writes a blob; accept text too
+            let data = row.get_ref(4)?.as_bytes()?.to_vec();
+            Ok((id, summary, updated_at, data_type, data))
+        })?;
+
+        let mut undecodable = 0;
+        for (id, summary, updated_at, data_type, data) in rows.flatten() {
+            let json = match data_type.as_deref() {
+                Some("zstd") => match zstd::decode_all(data.as_slice()) {
+                    Ok(json) => json,
+                    Err(_) => {
+                        undecodable += 1;
+                        continue;
+                    }
+                },
+                _ => data,
+            };
+            let Ok(thread) = serde_json::from_slice::<SerializedThread>(&json) else {
+                continue;
+            };
+            let Some(tokens) = thread.cumulative_token_usage else {
+                continue;
+            };
+
+            let model = thread.model.as_ref().and_then(|m| m.model.clone());
+            let mut usage = UsageData::new();
+            usage.input_tokens = tokens.input_tokens;
+            usage.output_tokens = tokens.output_tokens;
+            usage.cache_write_tokens = tokens.cache_creation_input_tokens;
+            usage.cache_read_tokens = tokens.cache_read_input_tokens;
+            usage.request_count = match &thread.request_token_usage {
+                Some(serde_json::Value::Array(requests)) => requests.len() as u64,
+                Some(serde_json::Value::Object(requests)) => requests.len() as u64,
+                _ => 0,
+            };
+            usage.estimated_cost = calculate_cost_with_cache(
+                usage.input_tokens,
+                usage.output_tokens,
+                usage.cache_read_tokens,
+                usage.cache_write_tokens,
+                model.as_deref(),
+            );
+
+            // A thread's usage is counted when it was last updated
+            let timestamp = updated_at
+                .as_deref()
+                .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+                .map(|dt| dt.with_timezone(&Utc));
+            stats.add_at(&usage, timestamp, ranges);
+
+            let model_label = match thread.model {
+                Some(SerializedModel { provider: Some(provider), model: Some(model) }) => format!("{}/{}", provider, model),
+                _ => model.unwrap_or_else(|| "unknown".to_string()),
+            };
+            breakdowns.by_model.entry(model_label).or_default().add_at(&usage, timestamp, ranges);
+
+            let thread_label = summary.filter(|s| !s.is_empty()).unwrap_or_else(|| "Untitled thread".to_string());
+            breakdowns
+                .by_thread
+                .entry(id)
+                .or_insert_with(|| (thread_label, UsageStats::default()))
+                .1
+                .add_at(&usage, timestamp, ranges);
+        }
+
+        Ok(undecodable)
+    }
+
+    /// The `TOP_THREADS` threads with the most tokens, then one slice for all the others
+    fn thread_breakdown(by_thread: HashMap<String, (String, UsageStats)>) -> Vec<UsageBreakdown> {
+        let mut threads: Vec<(String, UsageStats)> = by_thread.into_values().collect();
+        threads.sort_by_key(|(_, usage)| std::cmp::Reverse(usage.total.total_tokens()));
+
+        let others = threads.split_off(threads.len().min(TOP_THREADS));
+        let mut breakdown: Vec<UsageBreakdown> = threads
+            .into_iter()
+            .map(|(label, usage)| UsageBreakdown { dimension: "thread".to_string(), label, usage })
+            .collect();
+        if !others.is_empty() {
+            let mut usage = UsageStats::default();
+            for (_, thread) in &others {
+                usage.merge(thread);
+            }
+            breakdown.push(UsageBreakdown {
+                dimension: "thread".to_string(),
+                label: format!("{} other threads", others.len()),
+                usage,
+            });
+        }
+        breakdown
+    }
+}
+
+#[async_trait]
+impl Provider for ZedProvider {
+    fn name(&self) -> &'static str {
+        "zed"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Zed"
+    }
+
+    async fn is_available(&self) -> bool {
+        Self::threads_dbs().iter().any(|p| p.exists())
+    }
+
+    fn get_paths_to_check(&self) -> Vec<String> {
+        Self::threads_dbs()
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect()
+    }
+
+    fn data_source_type(&self) -> DataSourceType {
+        DataSourceType::LocalDb
+    }
+
+    async fn get_usage(&self, _time_range: Option<&TimeRange>) -> Result<ProviderResult> {
+        let db_paths: Vec<PathBuf> = Self::threads_dbs().into_iter().filter(|p| p.exists()).collect();
+        if db_paths.is_empty() {
+            return Ok(ProviderResult::not_found(self.name(), self.display_name()));
+        }
+
+        let ranges = Self::get_time_ranges();
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        let mut undecodable = 0;
+
+        for db_path in &db_paths {
+            match Self::process_database(db_path, &mut stats, &mut breakdowns, &ranges) {
+                Ok(skipped) => undecodable += skipped,
+                Err(e) => return Ok(ProviderResult::error(self.name(), self.display_name(), &e.to_string())),
+            }
+        }
+
+        let mut breakdown = UsageBreakdown::from_slices("model", breakdowns.by_model);
+        breakdown.extend(Self::thread_breakdown(breakdowns.by_thread));
+
+        let mut result = ProviderResult::active(self.name(), self.display_name(), stats, &paths::join_paths(&db_paths))
+            .with_breakdown(breakdown)
+            .with_note("Thread usage is counted when the thread was last updated");
+        if undecodable > 0 {
+            result = result.with_note(format!("{} compressed (zstd) threads could not be decoded", undecodable));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_plain_and_compressed_threads() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            "CREATE TABLE threads (id TEXT PRIMARY KEY, summary TEXT NOT NULL, updated_at TEXT NOT NULL, data_type TEXT NOT NULL, data BLOB NOT NULL)",
+        )
+        .unwrap();
+        let thread = |input: u64| {
+            serde_json::json!({
+                "model": { "provider": "anthropic", "model": "claude-sonnet-4" },
+                "cumulative_token_usage": { "input_tokens": input, "output_tokens": 10 },
+                "request_token_usage": { "m1": {}, "m2": {} },
+            })
+            .to_string()
+            .into_bytes()
+        };
+        let updated_at = Utc::now().to_rfc3339();
+        let insert = |id: &str, data_type: &str, data: Vec<u8>| {
+            conn.execute(
+                "INSERT INTO threads VALUES (?1, 'Fix the build', ?2, ?3, ?4)",
+                rusqlite::params![id, updated_at, data_type, data],
+            )
+            .unwrap();
+        };
+        insert("a", "json", thread(100));
+        insert("b", "zstd", zstd::encode_all(thread(200).as_slice(), 0).unwrap());
+        insert("c", "zstd", b"not zstd".to_vec());
+
+        let mut stats = UsageStats::default();
+        let mut breakdowns = Breakdowns::default();
+        let undecodable = ZedProvider::process_threads(&conn, &mut stats, &mut breakdowns, &get_local_time_ranges()).unwrap();
+
+        assert_eq!(undecodable, 1);
+        assert_eq!(stats.total.input_tokens, 300);
+        assert_eq!(stats.today.request_count, 4);
+        assert_eq!(breakdowns.by_model["anthropic/claude-sonnet-4"].total.input_tokens, 300);
+        // Threads sharing a summary stay apart
+        assert_eq!(breakdowns.by_thread.len(), 2);
+        assert_eq!(breakdowns.by_thread["b"].0, "Fix the build");
+        assert_eq!(breakdowns.by_thread["b"].1.total.input_tokens, 200);
+    }
+
+    #[test]
+    fn threads_beyond_the_top_are_grouped() {
+        let by_thread: HashMap<String, (String, UsageStats)> = (1..=TOP_THREADS as u64 + 3)
+            .map(|n| {
+                let mut usage = UsageStats::default();
+                usage.total.input_tokens = n * 100;
+                (format!("t{}", n), (format!("Thread {}", n), usage))
+            })
+            .collect();
+
+        let breakdown = ZedProvider::thread_breakdown(by_thread);
+
+        assert_eq!(breakdown.len(), TOP_THREADS + 1);
+        assert_eq!(breakdown[0].label, format!("Thread {}", TOP_THREADS + 3));
+        let other = breakdown.last().unwrap();
+        assert_eq!(other.label, "3 other threads");
+        assert_eq!(other.usage.total.input_tokens, 100 + 200 + 300);
+    }
+}
@@ -2,7 +2,9 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fmt;
+use std::path::PathBuf;
 
 /// Usage data for a single time period
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -42,6 +44,18 @@ impl UsageData {
         self.request_count += other.request_count;
         self.estimated_cost += other.estimated_cost;
     }
+
+    /// Usage beyond `other`, field by field, never below zero
+    pub fn saturating_sub(&self, other: &UsageData) -> UsageData {
+        UsageData {
+            input_tokens: self.input_tokens.saturating_sub(other.input_tokens),
+            output_tokens: self.output_tokens.saturating_sub(other.output_tokens),
+            cache_read_tokens: self.cache_read_tokens.saturating_sub(other.cache_read_tokens),
+            cache_write_tokens: self.cache_write_tokens.saturating_sub(other.cache_write_tokens),
+            request_count: self.request_count.saturating_sub(other.request_count),
+            estimated_cost: (self.estimated_cost - other.estimated_cost).max(0.0),
+        }
+    }
 }
 
 /// Usage statistics across time periods
@@ -53,6 +67,116 @@ pub struct UsageStats {
     pub total: UsageData,
 }
 
+impl UsageStats {
+    /// Add usage to the total and to every (today, week, month) range containing the timestamp
+    pub fn add_at(
+        &mut self,
+        usage: &UsageData,
+        timestamp: Option<DateTime<Utc>>,
+        ranges: &(TimeRange, TimeRange, TimeRange),
+    ) {
+        self.total.add(usage);
+
+        if let Some(ts) = timestamp {
+            if ranges.0.contains(ts) {
+                self.today.add(usage);
+            }
+            if ranges.1.contains(ts) {
+                self.this_week.add(usage);
+            }
+            if ranges.2.contains(ts) {
+                self.this_month.add(usage);
+            }
+        }
+    }
+
+    /// Add another set of stats, period by period
+    pub fn merge(&mut self, other: &UsageStats) {
+        self.today.add(&other.today);
+        self.this_week.add(&other.this_week);
+        self.this_month.add(&other.this_month);
+        self.total.add(&other.total);
+    }
+
+    /// Mutable access to each period, for filling in derived values like cost
+    pub fn periods_mut(&mut self) -> [&mut UsageData; 4] {
+        [&mut self.today, &mut self.this_week, &mut self.this_month, &mut self.total]
+    }
+
+    /// Replace each period's cost with the one in `costs` (e.g. billed amounts over estimates)
+    pub fn replace_costs(&mut self, costs: &UsageStats) {
+        for (period, cost) in self.periods_mut().into_iter().zip([
+            &costs.today,
+            &costs.this_week,
+            &costs.this_month,
+            &costs.total,
+        ]) {
+            period.estimated_cost = cost.estimated_cost;
+        }
+    }
+}
+
+/// Usage for one slice of a provider's data (e.g., one entrypoint or model)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBreakdown {
+    /// What the data is split by (e.g., "entrypoint", "mode", "model")
+    pub dimension: String,
+    /// Value of the dimension for this slice
+    pub label: String,
+    pub usage: UsageStats,
+}
+
+impl UsageBreakdown {
+    /// One slice per label, largest total first
+    pub fn from_slices(dimension: &str, slices: HashMap<String, UsageStats>) -> Vec<UsageBreakdown> {
+        let mut breakdown: Vec<UsageBreakdown> = slices
+            .into_iter()
+            .map(|(label, usage)| UsageBreakdown { dimension: dimension.to_string(), label, usage })
+            .collect();
+        breakdown.sort_by_key(|b| std::cmp::Reverse(b.usage.total.total_tokens()));
+        breakdown
+    }
+}
+
+/// Usage of one rolling rate-limit window, as last reported by the tool
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimit {
+    /// Which window the tool reported (e.g., "primary", "secondary")
+    pub name: String,
+    pub used_percent: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window_minutes: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resets_at: Option<DateTime<Utc>>,
+    /// When the tool recorded this snapshot
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub observed_at: Option<DateTime<Utc>>,
+}
+
+impl RateLimit {
+    /// Short label for the window length, e.g. "5h" or "7d"
+    pub fn window_label(&self) -> Option<String> {
+        self.window_minutes.map(|m| match m {
+            m if m >= 1440 && m % 1440 == 0 => format!("{}d", m / 1440),
+            m if m >= 60 && m % 60 == 0 => format!("{}h", m / 60),
+            m => format!("{}m", m),
+        })
+    }
+}
+
+/// Remaining prepaid credit on a service account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Balance {
+    /// ISO currency code, e.g. "USD"
+    pub currency: String,
+    pub remaining: f64,
+    /// Credit purchased or granted in total, if the service reports it
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    /// Below the configured low-balance threshold
+    pub low: bool,
+}
+
 /// Provider status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -69,6 +193,8 @@ pub enum ProviderStatus {
     AuthRequired,
     /// Parse/read error occurred
     Error,
+    /// Provider did not finish within the per-provider timeout
+    Timeout,
     /// Can only provide a link to web UI
     LinkOnly,
 }
@@ -82,6 +208,7 @@ impl fmt::Display for ProviderStatus {
             ProviderStatus::NoKey => write!(f, "No Key"),
             ProviderStatus::AuthRequired => write!(f, "Auth Required"),
             ProviderStatus::Error => write!(f, "Error"),
+            ProviderStatus::Timeout => write!(f, "Timeout"),
             ProviderStatus::LinkOnly => write!(f, "Link Only"),
         }
     }
@@ -105,6 +232,18 @@ pub struct ProviderResult {
     /// Data source path or description
     #[serde(skip_serializing_if = "Option::is_none")]
     pub data_source: Option<String>,
+    /// Extra diagnostic notes (shown in verbose mode)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>,
+    /// Optional sub-breakdown of the usage (shown with --breakdown)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub breakdown: Vec<UsageBreakdown>,
+    /// Rate-limit windows reported by the tool, most recent snapshot
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rate_limits: Vec<RateLimit>,
+    /// Remaining prepaid credit (prepaid-balance services only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub balance: Option<Balance>,
 }
 
 impl ProviderResult {
@@ -116,6 +255,10 @@ impl ProviderResult {
             usage: None,
             error: None,
             data_source: None,
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
         }
     }
 
@@ -127,6 +270,25 @@ impl ProviderResult {
             usage: None,
             error: Some(error.to_string()),
             data_source: None,
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
+        }
+    }
+
+    pub fn timeout(name: &str, display_name: &str, timeout: std::time::Duration) -> Self {
+        Self {
+            name: name.to_string(),
+            display_name: display_name.to_string(),
+            status: ProviderStatus::Timeout,
+            usage: None,
+            error: Some(format!("Timed out after {}s", timeout.as_secs())),
+            data_source: None,
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
         }
     }
 
@@ -138,6 +300,10 @@ impl ProviderResult {
             usage: None,
             error: None,
             data_source: None,
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
         }
     }
 
@@ -149,6 +315,10 @@ impl ProviderResult {
             usage: None,
             error: Some(message.to_string()),
             data_source: data_source.map(|s| s.to_string()),
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
         }
     }
 
@@ -160,6 +330,10 @@ impl ProviderResult {
             usage: None,
             error: None,
             data_source: Some(url.to_string()),
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
         }
     }
 
@@ -171,8 +345,87 @@ impl ProviderResult {
             usage: Some(usage),
             error: None,
             data_source: Some(data_source.to_string()),
+            notes: Vec::new(),
+            breakdown: Vec::new(),
+            rate_limits: Vec::new(),
+            balance: None,
         }
     }
+
+    /// Attach sub-breakdown slices to the result
+    pub fn with_breakdown(mut self, breakdown: Vec<UsageBreakdown>) -> Self {
+        self.breakdown.extend(breakdown);
+        self
+    }
+
+    /// Attach rate-limit windows to the result
+    pub fn with_rate_limits(mut self, rate_limits: Vec<RateLimit>) -> Self {
+        self.rate_limits.extend(rate_limits);
+        self
+    }
+
+    /// Attach the account's remaining balance to the result
+    pub fn with_balance(mut self, balance: Balance) -> Self {
+        self.balance = Some(balance);
+        self
+    }
+
+    /// Attach a diagnostic note to the result
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+}
+
+/// A single usage event with the context needed to attribute it (repository, branch, model)
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    /// Provider identifier that produced the record
+    pub provider: &'static str,
+    pub timestamp: DateTime<Utc>,
+    /// Working directory the tool was running in, if known
+    pub cwd: Option<PathBuf>,
+    /// Git branch recorded by the tool at the time of the request, if known
+    pub git_branch: Option<String>,
+    pub model: Option<String>,
+    /// Usage for this event; estimated_cost is always filled in
+    pub usage: UsageData,
+}
+
+/// Usage rolled up to a git repository and branch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepoUsage {
+    /// Repository root directory
+    pub repository: String,
+    pub branch: String,
+    /// Cost-center label (only when allocation rules are configured)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_center: Option<String>,
+    /// Providers that contributed usage
+    pub tools: Vec<String>,
+    pub usage: UsageStats,
+}
+
+/// Number of calls to one tool (or MCP server)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCount {
+    pub name: String,
+    pub calls: u64,
+}
+
+/// Tool-use and subagent analytics from Claude Code transcripts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolAnalytics {
+    /// Calls per tool name (built-in and MCP), most used first
+    pub tools: Vec<ToolCount>,
+    /// Calls per MCP server, most used first
+    pub mcp_servers: Vec<ToolCount>,
+    /// Usage from the main conversation thread
+    pub main_thread: UsageData,
+    /// Usage from subagent (sidechain) conversations
+    pub subagents: UsageData,
+    /// Directories scanned
+    pub data_source: String,
 }
 
 /// Time range for filtering usage data
@@ -199,7 +452,6 @@ pub struct ProviderMeta {
     pub requires_auth: bool,
 }
 
-#[allow(dead_code)]
 #[derive(Debug, Clone, Copy)]
 pub enum DataSourceType {
     LocalFile,
@@ -208,8 +460,16 @@ pub enum DataSourceType {
     LinkOnly,
 }
 
+impl DataSourceType {
+    /// Whether the data lives under the user's home directory
+    pub fn is_local(self) -> bool {
+        matches!(self, DataSourceType::LocalFile | DataSourceType::LocalDb)
+    }
+}
+
 /// CLI output format
-#[derive(Debug, Clone, Copy, Default, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     #[default]
     Table,
@@ -0,0 +1,340 @@
+//! Cost allocation rules: map usage to cost-center labels by project path, git remote or model
+//!
+//! Rules live in `~/.config/a2zusage/cost-centers.toml` (or the file given with `--rules`):
+//!
+//! ```toml
+//! default = "unallocated"
+//!
+//! [[rule]]
+//! label = "client-acme"
+//! path = "~/work/acme/**"
+//!
+//! [[rule]]
+//! label = "internal"
+//! remote = "*github.com*our-org/*"
+//!
+//! [[rule]]
+//! label = "personal"
+//! model = "*haiku*"
+//! ```
+//!
+//! Rules are checked in order and the first match wins. Every condition set on a rule must match.
+
+use crate::types::{ProviderResult, UsageBreakdown, UsageRecord, UsageStats};
+use crate::utils::git::{origin_url, RepoLocator};
+use crate::utils::paths;
+use crate::utils::time::get_local_time_ranges;
+use anyhow::{Context, Result};
+use glob::Pattern;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Label used when no rule matches and the file doesn't set `default`
+pub const DEFAULT_LABEL: &str = "unallocated";
+
+/// Breakdown dimension name for cost-center slices
+pub const COST_CENTER: &str = "cost_center";
+
+#[derive(Debug, Deserialize)]
+struct RulesFile {
+    default: Option<String>,
+    #[serde(default, rename = "rule")]
+    rules: Vec<RuleEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RuleEntry {
+    label: String,
+    path: Option<String>,
+    remote: Option<String>,
+    model: Option<String>,
+}
+
+struct Rule {
+    label: String,
+    path: Option<Pattern>,
+    remote: Option<Pattern>,
+    model: Option<Pattern>,
+}
+
+/// Loaded cost allocation rules
+pub struct AllocationRules {
+    rules: Vec<Rule>,
+    default_label: String,
+    locator: RepoLocator,
+    remotes: HashMap<PathBuf, Option<String>>,
+}
+
+fn compile(field: &str, label: &str, pattern: Option<&str>) -> Result<Option<Pattern>> {
+    pattern
+        .map(|p| Pattern::new(p).with_context(|| format!("invalid {} pattern {:?} in rule {:?}", field, p, label)))
+        .transpose()
+}
+
+impl AllocationRules {
+    /// Parse rules from TOML text
+    pub fn parse(content: &str) -> Result<Self> {
+        let file: RulesFile = toml::from_str(content)?;
+
+        let mut rules = Vec::with_capacity(file.rules.len());
+        for entry in file.rules {
+            if entry.path.is_none() && entry.remote.is_none() && entry.model.is_none() {
+                anyhow::bail!("rule {:?} needs at least one of path, remote or model", entry.label);
+            }
+            rules.push(Rule {
+                path: compile("path", &entry.label, entry.path.as_deref().map(paths::expand_home).as_deref())?,
+                remote: compile("remote", &entry.label, entry.remote.as_deref())?,
+                model: compile("model", &entry.label, entry.model.as_deref())?,
+                label: entry.label,
+            });
+        }
+
+        Ok(Self {
+            rules,
+            default_label: file.default.unwrap_or_else(|| DEFAULT_LABEL.to_string()),
+            locator: RepoLocator::new(),
+            remotes: HashMap::new(),
+        })
+    }
+
+    /// Load rules from `path`, or from the default location if `path` is None.
+    /// Returns None when no rules file exists at the default location.
+    pub fn load(path: Option<&Path>) -> Result<Option<Self>> {
+        let path = match path {
+            Some(p) => p.to_path_buf(),
+            None => match paths::a2zusage::cost_rules_file() {
+                Some(p) if p.exists() => p,
+                _ => return Ok(None),
+            },
+        };
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read rules file {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("failed to parse rules file {}", path.display()))
+            .map(Some)
+    }
+
+    /// Cost-center label for a usage record
+    pub fn label_for(&mut self, record: &UsageRecord) -> String {
+        self.label(record.cwd.as_deref(), record.model.as_deref())
+    }
+
+    /// Cost-center label for usage in `cwd` (if known) with `model` (if known)
+    fn label(&mut self, cwd: Option<&Path>, model: Option<&str>) -> String {
+        let repo_root = cwd.and_then(|c| self.locator.repo_root(c));
+        let remote = repo_root.and_then(|root| {
+            self.remotes
+                .entry(root.clone())
+                .or_insert_with(|| origin_url(&root))
+                .clone()
+        });
+
+        for rule in &self.rules {
+            let path_ok = match &rule.path {
+                // A pattern may name the project directory itself or a glob under it
+                Some(p) => cwd.map(|c| c.ancestors().any(|dir| p.matches_path(dir))).unwrap_or(false),
+                None => true,
+            };
+            let remote_ok = match &rule.remote {
+                Some(p) => remote.as_deref().map(|r| p.matches(r)).unwrap_or(false),
+                None => true,
+            };
+            let model_ok = match &rule.model {
+                Some(p) => model.map(|m| p.matches(m)).unwrap_or(false),
+                None => true,
+            };
+
+            if path_ok && remote_ok && model_ok {
+                return rule.label.clone();
+            }
+        }
+
+        self.default_label.clone()
+    }
+
+    /// Attach cost-center slices to each provider result.
+    /// Providers without attributable records are split by their per-model breakdown, so
+    /// `model` rules still apply. Either way, usage the records or model slices don't cover
+    /// (e.g. untimestamped items) goes to the default label.
+    pub fn apply(&mut self, results: &mut [ProviderResult], records: &[UsageRecord]) {
+        for result in results.iter_mut() {
+            let Some(ref usage) = result.usage else { continue };
+
+            let provider_records: Vec<UsageRecord> = records.iter()
+                .filter(|r| r.provider == result.name)
+                .cloned()
+                .collect();
+
+            let slices = if provider_records.is_empty() {
+                let by_model: Vec<(String, UsageStats)> = result.breakdown.iter()
+                    .filter(|b| b.dimension == "model")
+                    .map(|b| (b.label.clone(), b.usage.clone()))
+                    .collect();
+                self.breakdown_by_model(usage, &by_model)
+            } else {
+                self.breakdown(usage, &provider_records)
+            };
+            result.breakdown.extend(slices);
+        }
+    }
+
+    /// Split a provider's usage into per-label slices using its per-model slices
+    fn breakdown_by_model(&mut self, usage: &UsageStats, by_model: &[(String, UsageStats)]) -> Vec<UsageBreakdown> {
+        let mut slices: HashMap<String, UsageStats> = HashMap::new();
+
+        for (model, model_usage) in by_model {
+            let label = self.label(None, Some(model));
+            slices.entry(label).or_default().merge(model_usage);
+        }
+
+        self.with_remainder(usage, slices)
+    }
+
+    /// Split a provider's usage into per-label slices using its records
+    fn breakdown(&mut self, usage: &UsageStats, records: &[UsageRecord]) -> Vec<UsageBreakdown> {
+        let ranges = get_local_time_ranges();
+        let mut slices: HashMap<String, UsageStats> = HashMap::new();
+
+        for record in records {
+            let label = self.label_for(record);
+            slices.entry(label).or_default().add_at(&record.usage, Some(record.timestamp), &ranges);
+        }
+
+        self.with_remainder(usage, slices)
+    }
+
+    /// Add the part of `usage` the slices don't cover to the default label, most expensive first
+    fn with_remainder(&self, usage: &UsageStats, mut slices: HashMap<String, UsageStats>) -> Vec<UsageBreakdown> {
+        let mut allocated = UsageStats::default();
+        for slice in slices.values() {
+            allocated.merge(slice);
+        }
+
+        let remainder = UsageStats {
+            today: usage.today.saturating_sub(&allocated.today),
+            this_week: usage.this_week.saturating_sub(&allocated.this_week),
+            this_month: usage.this_month.saturating_sub(&allocated.this_month),
+            total: usage.total.saturating_sub(&allocated.total),
+        };
+        if remainder.total.total_tokens() > 0 || remainder.total.estimated_cost > 0.0 || slices.is_empty() {
+            slices.entry(self.default_label.clone()).or_default().merge(&remainder);
+        }
+
+        Self::sorted(slices)
+    }
+
+    fn sorted(slices: HashMap<String, UsageStats>) -> Vec<UsageBreakdown> {
+        let mut breakdown: Vec<UsageBreakdown> = slices
+            .into_iter()
+            .map(|(label, usage)| UsageBreakdown { dimension: COST_CENTER.to_string(), label, usage })
+            .collect();
+        breakdown.sort_by(|a, b| b.usage.total.estimated_cost.total_cmp(&a.usage.total.estimated_cost));
+        breakdown
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::UsageData;
+    use chrono::Utc;
+
+    const RULES: &str = r#"
+        default = "shared"
+
+        [[rule]]
+        label = "client-acme"
+        path = "/work/acme"
+
+        [[rule]]
+        label = "acme-haiku"
+        path = "/work/acme/**"
+        model = "*haiku*"
+
+        [[rule]]
+        label = "personal"
+        model = "*haiku*"
+    "#;
+
+    fn record(cwd: Option<&str>, model: Option<&str>) -> UsageRecord {
+        UsageRecord {
+            provider: "test",
+            timestamp: Utc::now(),
+            cwd: cwd.map(PathBuf::from),
+            git_branch: None,
+            model: model.map(str::to_string),
+            usage: UsageData::new(),
+        }
+    }
+
+    fn stats(input_tokens: u64) -> UsageStats {
+        let usage = UsageData { input_tokens, ..UsageData::default() };
+        UsageStats { today: usage.clone(), this_week: usage.clone(), this_month: usage.clone(), total: usage }
+    }
+
+    #[test]
+    fn first_matching_rule_wins() {
+        let mut rules = AllocationRules::parse(RULES).unwrap();
+        // The acme path rule comes first, so the later, more specific acme-haiku rule never applies
+        assert_eq!(rules.label_for(&record(Some("/work/acme/api"), Some("claude-3-5-haiku"))), "client-acme");
+        assert_eq!(rules.label_for(&record(Some("/home/me/notes"), Some("claude-3-5-haiku"))), "personal");
+    }
+
+    #[test]
+    fn path_pattern_matches_the_directory_and_below() {
+        let mut rules = AllocationRules::parse(RULES).unwrap();
+        assert_eq!(rules.label_for(&record(Some("/work/acme"), None)), "client-acme");
+        assert_eq!(rules.label_for(&record(Some("/work/acme/api/src"), None)), "client-acme");
+        assert_eq!(rules.label_for(&record(Some("/work/acme-other"), None)), "shared");
+    }
+
+    #[test]
+    fn unmatched_usage_gets_the_default_label() {
+        let mut rules = AllocationRules::parse(RULES).unwrap();
+        assert_eq!(rules.label_for(&record(None, Some("gpt-4o"))), "shared");
+
+        let mut rules = AllocationRules::parse("[[rule]]\nlabel = \"x\"\nmodel = \"gpt*\"").unwrap();
+        assert_eq!(rules.label_for(&record(None, Some("o3"))), DEFAULT_LABEL);
+    }
+
+    #[test]
+    fn rules_need_a_condition_and_valid_globs() {
+        assert!(AllocationRules::parse("[[rule]]\nlabel = \"empty\"").is_err());
+        assert!(AllocationRules::parse("[[rule]]\nlabel = \"bad\"\npath = \"/work/[acme\"").is_err());
+    }
+
+    #[test]
+    fn providers_without_records_are_split_by_model() {
+        let mut rules = AllocationRules::parse(RULES).unwrap();
+        let by_model = vec![("claude-3-5-haiku".to_string(), stats(100)), ("gpt-4o".to_string(), stats(50))];
+        // 30 tokens aren't covered by any model slice
+        let slices = rules.breakdown_by_model(&stats(180), &by_model);
+
+        let input = |label: &str| {
+            slices.iter().find(|s| s.label == label).map(|s| s.usage.total.input_tokens)
+        };
+        assert_eq!(input("personal"), Some(100));
+        assert_eq!(input("shared"), Some(80));
+        assert_eq!(slices.len(), 2);
+    }
+
+    #[test]
+    fn records_covering_part_of_the_usage_leave_a_default_remainder() {
+        let mut rules = AllocationRules::parse(RULES).unwrap();
+        let mut acme = record(Some("/work/acme/api"), Some("gpt-4o"));
+        acme.usage.input_tokens = 70;
+        let mut haiku = record(None, Some("claude-3-5-haiku"));
+        haiku.usage.input_tokens = 20;
+        // 10 tokens had no timestamp, so no record
+        let slices = rules.breakdown(&stats(100), &[acme, haiku]);
+
+        let input = |label: &str| slices.iter().find(|s| s.label == label).map(|s| s.usage.total.input_tokens);
+        assert_eq!(input("client-acme"), Some(70));
+        assert_eq!(input("personal"), Some(20));
+        assert_eq!(input("shared"), Some(10));
+        assert_eq!(slices.iter().map(|s| s.usage.today.input_tokens).sum::<u64>(), 100);
+    }
+}
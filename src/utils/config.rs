@@ -0,0 +1,302 @@
+//! User configuration: `~/.config/a2zusage/config.toml` (or the file given with `--config`)
+//!
+//! ```toml
+//! format = "json"          # default output format: table, json or csv
+//! timezone = "utc"         # day/week/month boundaries: local, utc or an offset like "+05:30"
+//! timeout = 60             # per-provider timeout in seconds
+//! breakdown = true         # show per-provider sub-breakdowns in the table
+//! color = false            # disable colored output
+//! homes = ["/mnt/c/Users/me", "~/backups/old-laptop"]   # extra home roots to scan (like --home)
+//!
+//! [providers.tabnine]
+//! enabled = false
+//!
+//! [providers.claude-code]
+//! paths = ["/mnt/backup/.claude/projects"]   # scanned in addition to the defaults
+//!
+//! [providers.cursor]
+//! paths = ["~/old-laptop/state.vscdb"]
+//! replace_paths = true                       # scan only `paths`, not the defaults
+//! timeout = 120                              # this provider's timeout, overriding the top-level one
+//!
+//! [providers.cline]
+//! extensions = ["acme.cline-fork"]           # more Cline-family extension IDs
+//!
+//! [providers.openai-codex]
+//! base_url = "http://localhost:8080"
+//!
+//! [providers.litellm]
+//! base_url = "https://litellm.internal.example.com"
+//! api_key_env = "LITELLM_MASTER_KEY"         # or api_key = "sk-..."
+//!
+//! [providers.openrouter]
+//! low_balance = 10.0                         # warn below $10 of prepaid credit (default $5, or its CNY equivalent)
+//! ```
+//!
+//! Command-line flags take precedence over the file. Unknown keys are rejected so typos surface.
+
+use crate::types::OutputFormat;
+use crate::utils::paths;
+use crate::utils::time::ReportTimeZone;
+use anyhow::{Context, Result};
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Low-balance warning threshold in US dollars when a provider doesn't configure one
+pub const DEFAULT_LOW_BALANCE: f64 = 5.0;
+
+static CONFIG: OnceCell<Config> = OnceCell::new();
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format: Option<OutputFormat>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timezone: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breakdown: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<bool>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub homes: Vec<PathBuf>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub providers: BTreeMap<String, ProviderConfig>,
+}
+
+/// Per-provider settings, keyed by provider ID (see `a2zusage list`)
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProviderConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>,
+    /// Extra locations of the provider's primary data (the first path listed by `doctor`)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<PathBuf>,
+    /// Use only `paths`, ignoring the default locations
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub replace_paths: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_url: Option<String>,
+    /// API key for services configured only through this file (prefer `api_key_env`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key: Option<String>,
+    /// Name of an environment variable holding the API key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub api_key_env: Option<String>,
+    /// Extra VS Code extension IDs to scan (Cline forks not in the built-in list)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+    /// Warn when a prepaid balance drops below this amount (in the balance's currency)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_balance: Option<f64>,
+    /// Timeout in seconds for this provider, overriding the top-level `timeout`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u64>,
+}
+
+impl Config {
+    /// Parse configuration from TOML text
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut config: Config = toml::from_str(content)?;
+
+        if let Some(ref tz) = config.timezone {
+            tz.parse::<ReportTimeZone>()?;
+        }
+        for home in config.homes.iter_mut() {
+            *home = PathBuf::from(paths::expand_home(&home.to_string_lossy()));
+        }
+        for provider in config.providers.values_mut() {
+            for path in provider.paths.iter_mut() {
+                *path = PathBuf::from(paths::expand_home(&path.to_string_lossy()));
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Load configuration from `path`, or from the default location if `path` is None.
+    /// A missing file at the default location yields the defaults.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(p) => p.to_path_buf(),
+            None => match paths::a2zusage::config_file() {
+                Some(p) if p.exists() => p,
+                _ => return Ok(Self::default()),
+            },
+        };
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("failed to read config file {}", path.display()))?;
+        Self::parse(&content).with_context(|| format!("failed to parse config file {}", path.display()))
+    }
+
+    pub fn provider(&self, name: &str) -> Option<&ProviderConfig> {
+        self.providers.get(name)
+    }
+
+    /// Providers are enabled unless the config turns them off
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.provider(name).and_then(|p| p.enabled).unwrap_or(true)
+    }
+
+    /// A provider's data locations: its defaults plus any configured `paths`,
+    /// or only the configured ones when `replace_paths` is set.
+    /// Configured paths belong to the real home, so alternative home roots only get the defaults.
+    pub fn data_paths(&self, name: &str, defaults: impl IntoIterator<Item = PathBuf>) -> Vec<PathBuf> {
+        let provider = match self.provider(name) {
+            Some(p) if paths::alternate_home().is_none() => p,
+            _ => return defaults.into_iter().collect(),
+        };
+
+        let mut paths: Vec<PathBuf> = if provider.replace_paths {
+            Vec::new()
+        } else {
+            defaults.into_iter().collect()
+        };
+        for extra in &provider.paths {
+            if !paths.contains(extra) {
+                paths.push(extra.clone());
+            }
+        }
+        paths
+    }
+
+    /// Like [`Config::data_paths`], for locations labeled by the editor (or tool) they belong to.
+    /// Configured paths are labeled "configured".
+    pub fn labeled_data_paths(&self, name: &str, defaults: Vec<(&'static str, PathBuf)>) -> Vec<(&'static str, PathBuf)> {
+        self.data_paths(name, defaults.iter().map(|(_, p)| p.clone()))
+            .into_iter()
+            .map(|path| {
+                let label = defaults
+                    .iter()
+                    .find(|(_, p)| *p == path)
+                    .map(|(label, _)| *label)
+                    .unwrap_or("configured");
+                (label, path)
+            })
+            .collect()
+    }
+
+    /// API base URL for a provider, without a trailing slash
+    pub fn base_url(&self, name: &str, default: &str) -> String {
+        self.provider(name)
+            .and_then(|p| p.base_url.as_deref())
+            .unwrap_or(default)
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    /// Configured base URL for a provider without a default endpoint
+    pub fn configured_base_url(&self, name: &str) -> Option<String> {
+        self.provider(name)
+            .and_then(|p| p.base_url.as_deref())
+            .map(|url| url.trim_end_matches('/').to_string())
+    }
+
+    /// API key from `api_key`, or from the environment variable named by `api_key_env`
+    pub fn api_key(&self, name: &str) -> Option<String> {
+        let provider = self.provider(name)?;
+        provider
+            .api_key
+            .clone()
+            .or_else(|| std::env::var(provider.api_key_env.as_deref()?).ok())
+            .filter(|k| !k.is_empty())
+    }
+
+    /// Extra extension IDs configured for a provider
+    pub fn extensions(&self, name: &str) -> &[String] {
+        self.provider(name).map(|p| p.extensions.as_slice()).unwrap_or_default()
+    }
+
+    /// Configured threshold below which a provider's prepaid balance is flagged as low,
+    /// in the balance's currency. Without one, providers fall back to [`DEFAULT_LOW_BALANCE`].
+    pub fn low_balance(&self, name: &str) -> Option<f64> {
+        self.provider(name).and_then(|p| p.low_balance)
+    }
+
+    /// Configured timeout in seconds for a provider: its own `timeout`, else the top-level one
+    pub fn timeout(&self, name: &str) -> Option<u64> {
+        self.provider(name).and_then(|p| p.timeout).or(self.timeout)
+    }
+
+    pub fn timezone(&self) -> ReportTimeZone {
+        self.timezone
+            .as_deref()
+            .and_then(|tz| tz.parse().ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Install the configuration for the rest of the run. Only the first call has an effect.
+pub fn init(config: Config) {
+    let _ = CONFIG.set(config);
+}
+
+/// The active configuration (defaults if none was installed)
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(Config::default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_the_documented_settings() {
+        let config = Config::parse(
+            r#"
+            format = "json"
+            timezone = "+05:30"
+            timeout = 60
+
+            [providers.tabnine]
+            enabled = false
+
+            [providers.cline]
+            extensions = ["acme.cline-fork"]
+
+            [providers.cursor]
+            timeout = 120
+            "#,
+        )
+        .unwrap();
+
+        assert!(matches!(config.format, Some(OutputFormat::Json)));
+        assert!(matches!(config.timezone(), ReportTimeZone::Fixed(_)));
+        assert_eq!(config.timeout("cline"), Some(60));
+        assert_eq!(config.timeout("cursor"), Some(120));
+        assert!(!config.is_enabled("tabnine"));
+        assert!(config.is_enabled("cursor"));
+        assert_eq!(config.extensions("cline"), ["acme.cline-fork"]);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        // Typos at the top level and inside a provider table both surface
+        assert!(Config::parse("timeuot = 60").is_err());
+        assert!(Config::parse("[providers.cursor]\npath = [\"/tmp/state.vscdb\"]").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_values() {
+        assert!(Config::parse("timezone = \"+25:00\"").is_err());
+        assert!(Config::parse("format = \"xml\"").is_err());
+        assert!(Config::parse("timeout = \"soon\"").is_err());
+    }
+
+    #[test]
+    fn replace_paths_drops_the_defaults() {
+        let config = Config::parse("[providers.cursor]\npaths = [\"/data/state.vscdb\"]\nreplace_paths = true").unwrap();
+        let paths = config.data_paths("cursor", [PathBuf::from("/default/state.vscdb")]);
+        assert_eq!(paths, [PathBuf::from("/data/state.vscdb")]);
+
+        let paths = config.data_paths("cline", [PathBuf::from("/default/tasks")]);
+        assert_eq!(paths, [PathBuf::from("/default/tasks")]);
+    }
+}
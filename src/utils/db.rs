@@ -1,11 +1,123 @@
 //! Safe SQLite database reading utilities
 //! Uses snapshot strategy to avoid SQLITE_BUSY errors
+//!
+//! Strategies are tried in order, cheapest first:
+//! 1. Open the live database read-only via a URI (`mode=ro`; `immutable=1` only when that is refused)
+//! 2. Copy it with SQLite's online backup API into a temporary file
+//! 3. Copy the database, `-wal` and `-shm` files byte-for-byte (legacy fallback)
 
 use anyhow::Result;
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, ErrorCode, OpenFlags};
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 
+/// Pages copied per backup step; small steps let a busy writer make progress between them
+const BACKUP_PAGES_PER_STEP: std::os::raw::c_int = 1024;
+
+/// Pause between backup steps when the source is busy
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(10);
+
+/// A readable connection to a consistent view of a SQLite database.
+/// Holds the temporary file (if any) so it lives as long as the connection.
+pub struct DbSnapshot {
+    conn: Connection,
+    _temp_file: Option<NamedTempFile>,
+}
+
+impl DbSnapshot {
+    pub fn connection(&self) -> &Connection {
+        &self.conn
+    }
+
+    /// Whether the connection reads the live database rather than a copy
+    pub fn is_in_place(&self) -> bool {
+        self._temp_file.is_none()
+    }
+}
+
+/// Build a SQLite URI for a filesystem path with the given query parameters.
+/// Escapes the characters SQLite treats specially in URI paths.
+fn sqlite_uri(path: &Path, params: &str) -> String {
+    let raw = path.to_string_lossy().replace('\\', "/");
+    let mut escaped = String::with_capacity(raw.len());
+    for c in raw.chars() {
+        match c {
+            '%' => escaped.push_str("%25"),
+            '?' => escaped.push_str("%3f"),
+            '#' => escaped.push_str("%23"),
+            _ => escaped.push(c),
+        }
+    }
+
+    // Windows drive paths (C:/...) need an empty authority: file:///C:/...
+    if escaped.starts_with('/') {
+        format!("file:{}?{}", escaped, params)
+    } else {
+        format!("file:///{}?{}", escaped, params)
+    }
+}
+
+fn open_uri_read_only(source_path: &Path, params: &str) -> Result<Connection> {
+    let conn = Connection::open_with_flags(
+        sqlite_uri(source_path, params),
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_URI | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )?;
+    // Opening is lazy; touch the schema so lock and format errors surface here
+    conn.query_row("SELECT COUNT(*) FROM sqlite_master", [], |row| row.get::<_, i64>(0))?;
+    Ok(conn)
+}
+
+/// Whether SQLite refused to open or lock the file (read-only media, no access to `-shm`)
+fn is_permission_error(e: &anyhow::Error) -> bool {
+    matches!(
+        e.downcast_ref::<rusqlite::Error>(),
+        Some(rusqlite::Error::SqliteFailure(failure, _))
+            if matches!(failure.code, ErrorCode::CannotOpen | ErrorCode::ReadOnly | ErrorCode::PermissionDenied)
+    )
+}
+
+/// Strategy 1: read the live file in place under a shared lock, so a writer using either a
+/// rollback journal or a WAL cannot change pages mid-read. `immutable=1` skips locking
+/// entirely and is only a fallback for files that cannot be locked, e.g. on read-only media.
+fn open_in_place(source_path: &Path) -> Result<Connection> {
+    match open_uri_read_only(source_path, "mode=ro") {
+        Err(e) if is_permission_error(&e) => open_uri_read_only(source_path, "immutable=1"),
+        result => result,
+    }
+}
+
+/// Strategy 2: use the online backup API, which retries pages the writer changes mid-copy.
+fn backup_to_temp(source_path: &Path) -> Result<(Connection, NamedTempFile)> {
+    let source = open_uri_read_only(source_path, "mode=ro")?;
+    let temp_file = new_temp_file()?;
+
+    {
+        let mut dest = Connection::open(temp_file.path())?;
+        let backup = Backup::new(&source, &mut dest)?;
+        backup.run_to_completion(BACKUP_PAGES_PER_STEP, BACKUP_STEP_PAUSE, None)?;
+    }
+
+    let conn = Connection::open_with_flags(temp_file.path(), OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    Ok((conn, temp_file))
+}
+
+/// Strategy 3: copy the database file (plus WAL and SHM) to a temporary location.
+fn copy_to_temp(source_path: &Path) -> Result<(Connection, NamedTempFile)> {
+    let temp_file = create_db_snapshot(source_path)?;
+    let conn = Connection::open(temp_file.path())?;
+    Ok((conn, temp_file))
+}
+
+fn new_temp_file() -> Result<NamedTempFile> {
+    Ok(tempfile::Builder::new()
+        .prefix("a2zusage-snapshot-")
+        .suffix(".db")
+        .tempfile()?)
+}
+
 /// Create a temporary snapshot of a SQLite database for safe reading.
 /// This prevents SQLITE_BUSY errors when the IDE has the database locked.
 /// Returns the path to the temporary snapshot file.
@@ -15,10 +127,7 @@ pub fn create_db_snapshot(source_path: &Path) -> Result<NamedTempFile> {
     }
 
     // Create a temporary file with .db extension
-    let temp_file = tempfile::Builder::new()
-        .prefix("a2zusage-snapshot-")
-        .suffix(".db")
-        .tempfile()?;
+    let temp_file = new_temp_file()?;
 
     // Copy the database file to temp
     fs::copy(source_path, temp_file.path())?;
@@ -40,13 +149,57 @@ pub fn create_db_snapshot(source_path: &Path) -> Result<NamedTempFile> {
     Ok(temp_file)
 }
 
+/// Open a consistent, read-only view of a SQLite database,
+/// falling back through progressively heavier strategies.
+pub fn open_db_snapshot(source_path: &Path) -> Result<DbSnapshot> {
+    if !source_path.exists() {
+        anyhow::bail!("Database file does not exist: {:?}", source_path);
+    }
+
+    if let Ok(conn) = open_in_place(source_path) {
+        return Ok(DbSnapshot { conn, _temp_file: None });
+    }
+
+    if let Ok((conn, temp_file)) = backup_to_temp(source_path) {
+        return Ok(DbSnapshot { conn, _temp_file: Some(temp_file) });
+    }
+
+    let (conn, temp_file) = copy_to_temp(source_path)?;
+    Ok(DbSnapshot { conn, _temp_file: Some(temp_file) })
+}
+
 /// Execute a function with a database snapshot, ensuring cleanup.
-/// The temporary files are automatically cleaned up when the returned TempFile is dropped.
+/// Any temporary files are automatically cleaned up when the snapshot is dropped.
+/// When the live file is read in place, `f` runs inside a deferred read transaction, so
+/// all its queries see the same state even if a writer commits in between.
 pub fn with_db_snapshot<F, T>(source_path: &Path, f: F) -> Result<T>
 where
-    F: FnOnce(&Path) -> Result<T>,
+    F: FnOnce(&Connection) -> Result<T>,
 {
-    let snapshot = create_db_snapshot(source_path)?;
-    f(snapshot.path())
+    let snapshot = open_db_snapshot(source_path)?;
+    let conn = snapshot.connection();
+    // Dropping the transaction rolls it back, which only releases the read lock
+    let _read = if snapshot.is_in_place() { Some(conn.unchecked_transaction()?) } else { None };
+    f(conn)
     // snapshot is automatically cleaned up when dropped
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_place_reads_run_in_one_transaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state.db");
+        Connection::open(&path).unwrap().execute_batch("CREATE TABLE t (x INTEGER); INSERT INTO t VALUES (1)").unwrap();
+
+        let count = with_db_snapshot(&path, |conn| {
+            assert!(!conn.is_autocommit());
+            Ok(conn.query_row("SELECT COUNT(*) FROM t", [], |row| row.get::<_, i64>(0))?)
+        })
+        .unwrap();
+
+        assert_eq!(count, 1);
+    }
+}
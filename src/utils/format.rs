@@ -1,6 +1,8 @@
 //! Output formatting utilities
 
-use crate::types::{ProviderResult, ProviderStatus, UsageData};
+use crate::types::{Balance, ProviderResult, ProviderStatus, RepoUsage, ToolAnalytics, UsageData, UsageStats};
+use crate::utils::allocation::COST_CENTER;
+use std::collections::BTreeMap;
 use colored::Colorize;
 use tabled::{
     settings::{object::Columns, Alignment, Modify, Style},
@@ -29,6 +31,10 @@ pub fn format_tokens(data: &UsageData) -> String {
     let display_tokens = data.input_tokens + data.output_tokens;
     
     if display_tokens == 0 && data.request_count == 0 {
+        // Spend-only services (e.g. prepaid credit APIs) report dollars but no tokens
+        if data.estimated_cost > 0.0 {
+            return format_cost(data.estimated_cost);
+        }
         return "-".to_string();
     }
 
@@ -39,8 +45,17 @@ pub fn format_tokens(data: &UsageData) -> String {
     format!("{} tokens", format_number(display_tokens))
 }
 
+/// Format a remaining prepaid balance, e.g. "$12.34 left" or "¥80.00 left"
+pub fn format_balance(balance: &Balance) -> String {
+    let amount = match balance.currency.as_str() {
+        "USD" => format!("${:.2}", balance.remaining),
+        "CNY" => format!("¥{:.2}", balance.remaining),
+        other => format!("{:.2} {}", balance.remaining, other),
+    };
+    format!("{} left", amount)
+}
+
 /// Format cost as USD
-#[allow(dead_code)]
 pub fn format_cost(cost: f64) -> String {
     if cost == 0.0 {
         return "-".to_string();
@@ -62,6 +77,7 @@ pub fn format_status_plain(status: ProviderStatus) -> &'static str {
         ProviderStatus::NoKey => "No Key",
         ProviderStatus::AuthRequired => "Auth",
         ProviderStatus::Error => "Error",
+        ProviderStatus::Timeout => "Timeout",
         ProviderStatus::LinkOnly => "Link",
     }
 }
@@ -75,6 +91,7 @@ pub fn status_icon(status: ProviderStatus) -> &'static str {
         ProviderStatus::NoKey => "[x]",
         ProviderStatus::AuthRequired => "[!]",
         ProviderStatus::Error => "[x]",
+        ProviderStatus::Timeout => "[-]",
         ProviderStatus::LinkOnly => "[>]",
     }
 }
@@ -96,8 +113,9 @@ pub struct TableRow {
     pub total: String,
 }
 
-/// Format results as a table
-pub fn format_table(results: &[ProviderResult]) -> String {
+/// Format results as a table.
+/// With `show_breakdown`, each provider's breakdown slices are listed as indented rows below it.
+pub fn format_table(results: &[ProviderResult], show_breakdown: bool) -> String {
     let mut rows: Vec<TableRow> = Vec::new();
 
     for result in results {
@@ -120,6 +138,30 @@ pub fn format_table(results: &[ProviderResult]) -> String {
             this_month: month,
             total,
         });
+
+        if let Some(ref balance) = result.balance {
+            rows.push(TableRow {
+                tool: "  └ balance".to_string(),
+                status: if balance.low { "[!] Low balance".to_string() } else { String::new() },
+                today: String::new(),
+                this_week: String::new(),
+                this_month: String::new(),
+                total: format_balance(balance),
+            });
+        }
+
+        if show_breakdown {
+            for slice in &result.breakdown {
+                rows.push(TableRow {
+                    tool: format!("  └ {}: {}", slice.dimension, slice.label),
+                    status: String::new(),
+                    today: format_tokens(&slice.usage.today),
+                    this_week: format_tokens(&slice.usage.this_week),
+                    this_month: format_tokens(&slice.usage.this_month),
+                    total: format_tokens(&slice.usage.total),
+                });
+            }
+        }
     }
 
     let table = Table::new(rows)
@@ -137,7 +179,9 @@ pub fn format_table(results: &[ProviderResult]) -> String {
         .replace("[ ] N/A", &format!("{} {}", "[ ]".dimmed(), "N/A".dimmed()))
         .replace("[x] No Key", &format!("{} {}", "[x]".yellow(), "No Key".yellow()))
         .replace("[!] Auth", &format!("{} {}", "[!]".yellow(), "Auth".yellow()))
+        .replace("[!] Low balance", &format!("{} {}", "[!]".yellow(), "Low balance".yellow()))
         .replace("[x] Error", &format!("{} {}", "[x]".red(), "Error".red()))
+        .replace("[-] Timeout", &format!("{} {}", "[-]".yellow(), "Timeout".yellow()))
         .replace("[>] Link", &format!("{} {}", "[>]".blue(), "Link".blue()));
 
     colored_table
@@ -148,44 +192,344 @@ pub fn format_json(results: &[ProviderResult]) -> String {
     serde_json::to_string_pretty(results).unwrap_or_else(|_| "[]".to_string())
 }
 
+/// Quote a CSV field when it holds a comma, quote or line break (RFC 4180)
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
 /// Format results as CSV
+/// When allocation rules are configured, each provider is split into one row per cost center.
 pub fn format_csv(results: &[ProviderResult]) -> String {
     let mut output = String::from(
-        "Tool,Status,Today Input,Today Output,Month Input,Month Output,Total Input,Total Output,Est Cost\n",
+        "Tool,Status,Today Input,Today Output,Month Input,Month Output,Total Input,Total Output,Est Cost,Cost Center\n",
     );
 
     for result in results {
-        let (ti, to, mi, mo, toi, too, cost) = if let Some(ref usage) = result.usage {
-            (
-                usage.today.input_tokens,
-                usage.today.output_tokens,
-                usage.this_month.input_tokens,
-                usage.this_month.output_tokens,
-                usage.total.input_tokens,
-                usage.total.output_tokens,
-                usage.total.estimated_cost,
-            )
+        let cost_centers: Vec<_> = result.breakdown.iter()
+            .filter(|b| b.dimension == COST_CENTER)
+            .collect();
+
+        let rows: Vec<(Option<&UsageStats>, &str)> = if cost_centers.is_empty() {
+            vec![(result.usage.as_ref(), "")]
         } else {
-            (0, 0, 0, 0, 0, 0, 0.0)
+            cost_centers.iter().map(|b| (Some(&b.usage), b.label.as_str())).collect()
         };
 
+        for (usage, label) in rows {
+            let (ti, to, mi, mo, toi, too, cost) = if let Some(usage) = usage {
+                (
+                    usage.today.input_tokens,
+                    usage.today.output_tokens,
+                    usage.this_month.input_tokens,
+                    usage.this_month.output_tokens,
+                    usage.total.input_tokens,
+                    usage.total.output_tokens,
+                    usage.total.estimated_cost,
+                )
+            } else {
+                (0, 0, 0, 0, 0, 0, 0.0)
+            };
+
+            output.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{:.2},{}\n",
+                result.display_name,
+                result.status,
+                ti,
+                to,
+                mi,
+                mo,
+                toi,
+                too,
+                cost,
+                csv_field(label)
+            ));
+        }
+    }
+
+    output
+}
+
+/// Table row for cost-center totals
+#[derive(Tabled)]
+struct CostCenterRow {
+    #[tabled(rename = "Cost Center")]
+    label: String,
+    #[tabled(rename = "Tools")]
+    tools: String,
+    #[tabled(rename = "Today")]
+    today: String,
+    #[tabled(rename = "This Week")]
+    this_week: String,
+    #[tabled(rename = "This Month")]
+    this_month: String,
+    #[tabled(rename = "Total")]
+    total: String,
+    #[tabled(rename = "Est Cost")]
+    cost: String,
+}
+
+/// Format usage summed per cost center across all providers
+pub fn format_cost_centers_table(results: &[ProviderResult]) -> String {
+    let mut totals: BTreeMap<&str, (Vec<&str>, UsageStats)> = BTreeMap::new();
+
+    for result in results {
+        for slice in result.breakdown.iter().filter(|b| b.dimension == COST_CENTER) {
+            let (tools, usage) = totals.entry(slice.label.as_str()).or_default();
+            tools.push(result.display_name.as_str());
+            for (sum, part) in usage.periods_mut().into_iter().zip([
+                &slice.usage.today,
+                &slice.usage.this_week,
+                &slice.usage.this_month,
+                &slice.usage.total,
+            ]) {
+                sum.add(part);
+            }
+        }
+    }
+
+    let rows: Vec<CostCenterRow> = totals.into_iter()
+        .map(|(label, (tools, usage))| CostCenterRow {
+            label: label.to_string(),
+            tools: tools.join(", "),
+            today: format_tokens(&usage.today),
+            this_week: format_tokens(&usage.this_week),
+            this_month: format_tokens(&usage.this_month),
+            total: format_tokens(&usage.total),
+            cost: format_cost(usage.total.estimated_cost),
+        })
+        .collect();
+
+    Table::new(rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(2..)).with(Alignment::right()))
+        .to_string()
+}
+
+/// Table row for a rate-limit window
+#[derive(Tabled)]
+struct RateLimitRow {
+    #[tabled(rename = "Tool")]
+    tool: String,
+    #[tabled(rename = "Window")]
+    window: String,
+    #[tabled(rename = "Used")]
+    used: String,
+    #[tabled(rename = "Resets")]
+    resets: String,
+}
+
+/// Format the rate-limit windows reported by providers
+pub fn format_rate_limits_table(results: &[ProviderResult]) -> String {
+    let rows: Vec<RateLimitRow> = results.iter()
+        .flat_map(|result| result.rate_limits.iter().map(move |limit| RateLimitRow {
+            tool: result.display_name.clone(),
+            window: match limit.window_label() {
+                Some(window) => format!("{} ({})", limit.name, window),
+                None => limit.name.clone(),
+            },
+            used: format!("{:.0}%", limit.used_percent),
+            resets: limit.resets_at
+                .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_else(|| "-".to_string()),
+        }))
+        .collect();
+
+    Table::new(rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(2..3)).with(Alignment::right()))
+        .to_string()
+}
+
+/// Table row for tool call counts
+#[derive(Tabled)]
+struct ToolRow {
+    #[tabled(rename = "Tool")]
+    name: String,
+    #[tabled(rename = "Calls")]
+    calls: u64,
+}
+
+/// Table row for main thread vs subagent usage
+#[derive(Tabled)]
+struct ThreadRow {
+    #[tabled(rename = "Thread")]
+    thread: String,
+    #[tabled(rename = "Tokens")]
+    tokens: String,
+    #[tabled(rename = "Token Share")]
+    token_share: String,
+    #[tabled(rename = "Est Cost")]
+    cost: String,
+    #[tabled(rename = "Cost Share")]
+    cost_share: String,
+}
+
+fn format_share(part: f64, whole: f64) -> String {
+    if whole == 0.0 {
+        return "-".to_string();
+    }
+    format!("{:.1}%", part / whole * 100.0)
+}
+
+/// Format Claude Code tool analytics as tables
+pub fn format_tools_table(analytics: &ToolAnalytics) -> String {
+    let mut output = String::new();
+
+    let tool_rows: Vec<ToolRow> = analytics.tools.iter()
+        .map(|t| ToolRow { name: t.name.clone(), calls: t.calls })
+        .collect();
+    output.push_str(&format!("{}\n", "Tool Calls".bold()));
+    output.push_str(&Table::new(tool_rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::single(1)).with(Alignment::right()))
+        .to_string());
+
+    if !analytics.mcp_servers.is_empty() {
+        let server_rows: Vec<ToolRow> = analytics.mcp_servers.iter()
+            .map(|t| ToolRow { name: t.name.clone(), calls: t.calls })
+            .collect();
+        output.push_str(&format!("\n\n{}\n", "MCP Servers".bold()));
+        output.push_str(&Table::new(server_rows)
+            .with(Style::rounded())
+            .with(Modify::new(Columns::single(1)).with(Alignment::right()))
+            .to_string());
+    }
+
+    // Shares use all tokens (including cache) since that is what drives cost
+    let total_tokens = (analytics.main_thread.total_tokens() + analytics.subagents.total_tokens()) as f64;
+    let total_cost = analytics.main_thread.estimated_cost + analytics.subagents.estimated_cost;
+    let thread_rows: Vec<ThreadRow> = [("Main thread", &analytics.main_thread), ("Subagents", &analytics.subagents)]
+        .into_iter()
+        .map(|(thread, usage)| ThreadRow {
+            thread: thread.to_string(),
+            tokens: format_number(usage.total_tokens()),
+            token_share: format_share(usage.total_tokens() as f64, total_tokens),
+            cost: format_cost(usage.estimated_cost),
+            cost_share: format_share(usage.estimated_cost, total_cost),
+        })
+        .collect();
+    output.push_str(&format!("\n\n{}\n", "Main Thread vs Subagents".bold()));
+    output.push_str(&Table::new(thread_rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(1..)).with(Alignment::right()))
+        .to_string());
+
+    output
+}
+
+/// Format Claude Code tool analytics as JSON
+pub fn format_tools_json(analytics: &ToolAnalytics) -> String {
+    serde_json::to_string_pretty(analytics).unwrap_or_else(|_| "{}".to_string())
+}
+
+/// Format Claude Code tool call counts as CSV
+pub fn format_tools_csv(analytics: &ToolAnalytics) -> String {
+    let mut output = String::from("Kind,Name,Calls\n");
+    for tool in &analytics.tools {
+        output.push_str(&format!("tool,{},{}\n", csv_field(&tool.name), tool.calls));
+    }
+    for server in &analytics.mcp_servers {
+        output.push_str(&format!("mcp_server,{},{}\n", csv_field(&server.name), server.calls));
+    }
+    output
+}
+
+/// Table row for per-repository usage
+#[derive(Tabled)]
+struct RepoRow {
+    #[tabled(rename = "Repository")]
+    repository: String,
+    #[tabled(rename = "Branch")]
+    branch: String,
+    #[tabled(rename = "Cost Center")]
+    cost_center: String,
+    #[tabled(rename = "Today")]
+    today: String,
+    #[tabled(rename = "This Week")]
+    this_week: String,
+    #[tabled(rename = "This Month")]
+    this_month: String,
+    #[tabled(rename = "Total")]
+    total: String,
+    #[tabled(rename = "Est Cost")]
+    cost: String,
+}
+
+/// Format per-repository usage as a table
+pub fn format_repos_table(repos: &[RepoUsage]) -> String {
+    let rows: Vec<RepoRow> = repos.iter()
+        .map(|r| RepoRow {
+            repository: r.repository.clone(),
+            branch: r.branch.clone(),
+            cost_center: r.cost_center.clone().unwrap_or_else(|| "-".to_string()),
+            today: format_tokens(&r.usage.today),
+            this_week: format_tokens(&r.usage.this_week),
+            this_month: format_tokens(&r.usage.this_month),
+            total: format_tokens(&r.usage.total),
+            cost: format_cost(r.usage.total.estimated_cost),
+        })
+        .collect();
+
+    Table::new(rows)
+        .with(Style::rounded())
+        .with(Modify::new(Columns::new(3..)).with(Alignment::right()))
+        .to_string()
+}
+
+/// Format per-repository usage as JSON
+pub fn format_repos_json(repos: &[RepoUsage]) -> String {
+    serde_json::to_string_pretty(repos).unwrap_or_else(|_| "[]".to_string())
+}
+
+/// Format per-repository usage as CSV
+pub fn format_repos_csv(repos: &[RepoUsage]) -> String {
+    let mut output = String::from(
+        "Repository,Branch,Tools,Month Input,Month Output,Total Input,Total Output,Est Cost,Cost Center\n",
+    );
+
+    for repo in repos {
         output.push_str(&format!(
-            "{},{},{},{},{},{},{},{},{:.2}\n",
-            result.display_name,
-            result.status,
-            ti,
-            to,
-            mi,
-            mo,
-            toi,
-            too,
-            cost
+            "{},{},{},{},{},{},{},{:.2},{}\n",
+            csv_field(&repo.repository),
+            csv_field(&repo.branch),
+            csv_field(&repo.tools.join(";")),
+            repo.usage.this_month.input_tokens,
+            repo.usage.this_month.output_tokens,
+            repo.usage.total.input_tokens,
+            repo.usage.total.output_tokens,
+            repo.usage.total.estimated_cost,
+            csv_field(repo.cost_center.as_deref().unwrap_or(""))
         ));
     }
 
     output
 }
 
+/// Format a token count compactly for commit trailers (e.g., "412k", "1.2M")
+fn format_trailer_tokens(num: u64) -> String {
+    if num >= 1_000_000 {
+        format!("{:.1}M", num as f64 / 1_000_000.0)
+    } else if num >= 1_000 {
+        format!("{}k", num / 1_000)
+    } else {
+        num.to_string()
+    }
+}
+
+/// Build `AI-*` commit trailers summarizing usage since the previous commit.
+/// Token counts include cache tokens, matching what the cost is based on.
+pub fn format_git_trailers(usage: &UsageData, tools: &[String]) -> Vec<(String, String)> {
+    vec![
+        ("AI-Tokens".to_string(), format_trailer_tokens(usage.total_tokens())),
+        ("AI-Cost".to_string(), format!("${:.2}", usage.estimated_cost)),
+        ("AI-Tools".to_string(), tools.join(", ")),
+    ]
+}
+
 /// Print banner
 pub fn print_banner() {
     println!();
@@ -212,3 +556,28 @@ pub fn print_doctor_results(checks: &[(String, String, bool)]) {
         println!("    {}\n", path_display);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repos_csv_quotes_fields_with_separators() {
+        let mut usage = UsageStats::default();
+        usage.total.input_tokens = 1000;
+        let repos = [RepoUsage {
+            repository: "/work/acme, inc/api".to_string(),
+            branch: "fix/\"quoted\"".to_string(),
+            cost_center: Some("client-acme".to_string()),
+            tools: vec!["claude-code".to_string(), "aider".to_string()],
+            usage,
+        }];
+
+        let csv = format_repos_csv(&repos);
+
+        assert_eq!(
+            csv.lines().nth(1),
+            Some("\"/work/acme, inc/api\",\"fix/\"\"quoted\"\"\",claude-code;aider,0,0,1000,0,0.00,client-acme")
+        );
+    }
+}
@@ -0,0 +1,156 @@
+//! Git repository discovery for attributing usage to repositories and branches
+
+use crate::types::{RepoUsage, TimeRange, UsageData, UsageRecord, UsageStats};
+use crate::utils::allocation::AllocationRules;
+use crate::utils::time::get_local_time_ranges;
+use anyhow::Result;
+use chrono::{DateTime, TimeZone, Utc};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Find the root of the git repository containing `path` by walking up its ancestors.
+/// A `.git` entry may be a directory (regular clone) or a file (worktree/submodule).
+pub fn find_repo_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Caches repository lookups, since many usage records share a working directory
+#[derive(Default)]
+pub struct RepoLocator {
+    cache: HashMap<PathBuf, Option<PathBuf>>,
+}
+
+impl RepoLocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn repo_root(&mut self, path: &Path) -> Option<PathBuf> {
+        self.cache
+            .entry(path.to_path_buf())
+            .or_insert_with(|| find_repo_root(path))
+            .clone()
+    }
+}
+
+/// Roll usage records up to (repository root, branch), and cost center when rules are given.
+/// Records without a working directory inside a git repository are skipped.
+/// Sorted by total estimated cost, highest first.
+pub fn group_by_repo(records: &[UsageRecord], mut rules: Option<&mut AllocationRules>) -> Vec<RepoUsage> {
+    let ranges = get_local_time_ranges();
+    let mut locator = RepoLocator::new();
+    type GroupKey = (PathBuf, String, Option<String>);
+    let mut groups: HashMap<GroupKey, (BTreeSet<&'static str>, UsageStats)> = HashMap::new();
+
+    for record in records {
+        let Some(root) = record.cwd.as_deref().and_then(|cwd| locator.repo_root(cwd)) else {
+            continue;
+        };
+        let branch = record.git_branch.clone().unwrap_or_else(|| "(unknown)".to_string());
+        let label = rules.as_deref_mut().map(|r| r.label_for(record));
+
+        let (tools, usage) = groups.entry((root, branch, label)).or_default();
+        tools.insert(record.provider);
+        usage.add_at(&record.usage, Some(record.timestamp), &ranges);
+    }
+
+    let mut repos: Vec<RepoUsage> = groups
+        .into_iter()
+        .map(|((root, branch, cost_center), (tools, usage))| RepoUsage {
+            repository: root.to_string_lossy().to_string(),
+            branch,
+            cost_center,
+            tools: tools.into_iter().map(str::to_string).collect(),
+            usage,
+        })
+        .collect();
+    repos.sort_by(|a, b| b.usage.total.estimated_cost.total_cmp(&a.usage.total.estimated_cost));
+    repos
+}
+
+/// URL of the repository's `origin` remote, if configured
+pub fn origin_url(repo_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .args(["config", "--get", "remote.origin.url"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let url = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!url.is_empty()).then_some(url)
+}
+
+/// Timestamp of the most recent commit in the repository, or None if there are no commits yet
+pub fn last_commit_time(repo_root: &Path) -> Option<DateTime<Utc>> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_root)
+        .args(["log", "-1", "--format=%ct"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let secs: i64 = String::from_utf8_lossy(&output.stdout).trim().parse().ok()?;
+    Utc.timestamp_opt(secs, 0).single()
+}
+
+/// Resolve symlinks and `..` so the same repository reached through different paths compares equal
+fn canonical(path: &Path) -> PathBuf {
+    path.canonicalize().unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Sum the usage recorded inside `repo_root` within `range`.
+/// Returns the combined usage and the providers that contributed to it.
+pub fn usage_in_repo(records: &[UsageRecord], repo_root: &Path, range: &TimeRange) -> (UsageData, Vec<String>) {
+    let repo_root = canonical(repo_root);
+    let mut locator = RepoLocator::new();
+    let mut same_repo: HashMap<PathBuf, bool> = HashMap::new();
+    let mut usage = UsageData::new();
+    let mut tools: BTreeSet<&'static str> = BTreeSet::new();
+
+    for record in records {
+        if !range.contains(record.timestamp) {
+            continue;
+        }
+        let in_repo = record.cwd.as_deref()
+            .and_then(|cwd| locator.repo_root(cwd))
+            .map(|root| *same_repo.entry(root).or_insert_with_key(|root| canonical(root) == repo_root))
+            .unwrap_or(false);
+        if in_repo {
+            usage.add(&record.usage);
+            tools.insert(record.provider);
+        }
+    }
+
+    (usage, tools.into_iter().map(str::to_string).collect())
+}
+
+/// Append trailers to a commit message file using `git interpret-trailers`,
+/// which places them after any existing trailers and before comment lines.
+pub fn append_trailers(msg_file: &Path, trailers: &[(String, String)]) -> Result<()> {
+    let mut cmd = Command::new("git");
+    cmd.args(["interpret-trailers", "--in-place", "--if-exists", "replace"]);
+    for (key, value) in trailers {
+        cmd.arg("--trailer").arg(format!("{}: {}", key, value));
+    }
+    cmd.arg(msg_file);
+
+    let status = cmd.status()?;
+    if !status.success() {
+        anyhow::bail!("git interpret-trailers failed with {}", status);
+    }
+    Ok(())
+}
@@ -0,0 +1,118 @@
+//! Alternative home roots: devcontainer homes, WSL mounts (`/mnt/c/Users/me`), backups of old machines.
+//!
+//! Each local provider runs once for the real home and once per alternative root, with every
+//! path resolving under that root. The results are merged into one row per provider, and each
+//! root that contributed usage gets a `home` breakdown slice.
+
+use crate::types::{ProviderResult, ProviderStatus, UsageBreakdown, UsageStats};
+use crate::utils::{config, paths};
+use std::collections::HashSet;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use tokio::task::JoinHandle;
+
+/// Breakdown dimension name for per-home-root slices
+pub const HOME: &str = "home";
+
+/// Alternative roots from `--home` and the config file's `homes`, without duplicates.
+/// The real home is always scanned, so it is never part of this list.
+pub fn alternate_roots(cli_homes: &[PathBuf]) -> Vec<PathBuf> {
+    let canonical = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+    let mut seen: HashSet<PathBuf> = paths::home_dir().map(|h| canonical(&h)).into_iter().collect();
+
+    cli_homes
+        .iter()
+        .chain(&config::get().homes)
+        .filter(|home| seen.insert(canonical(home)))
+        .cloned()
+        .collect()
+}
+
+/// Spawn `f` on its own task with paths resolving under `home` (None for the real home).
+/// Local providers scan files and SQLite synchronously, so with `blocking` the task runs on the
+/// blocking pool: a scan stalled on a hung mount then ties up a pool thread, not an async worker.
+pub fn spawn_in<F>(home: Option<PathBuf>, blocking: bool, f: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let task = async move {
+        match home {
+            Some(home) => paths::with_home(home, f).await,
+            None => f.await,
+        }
+    };
+    if blocking {
+        let runtime = tokio::runtime::Handle::current();
+        tokio::task::spawn_blocking(move || runtime.block_on(task))
+    } else {
+        tokio::spawn(task)
+    }
+}
+
+fn label(root: Option<&Path>) -> String {
+    root.map(Path::to_path_buf)
+        .or_else(paths::home_dir)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_else(|| "~".to_string())
+}
+
+fn add_slice(breakdown: &mut Vec<UsageBreakdown>, slice: UsageBreakdown) {
+    match breakdown.iter_mut().find(|b| b.dimension == slice.dimension && b.label == slice.label) {
+        Some(existing) => existing.usage.merge(&slice.usage),
+        None => breakdown.push(slice),
+    }
+}
+
+/// Combine one provider's results for the real home (None, first) and each alternative root.
+/// Usage and breakdowns are summed; failures under a root that didn't stop the others become notes.
+pub fn merge(results: Vec<(Option<PathBuf>, ProviderResult)>) -> ProviderResult {
+    if results.len() == 1 {
+        return results.into_iter().next().map(|(_, r)| r).expect("one result");
+    }
+
+    let Some(template) = results.iter().map(|(_, r)| r).find(|r| r.usage.is_some()).cloned() else {
+        // Nothing found anywhere: prefer an error or timeout over "not found"
+        return results
+            .into_iter()
+            .map(|(_, r)| r)
+            .min_by_key(|r| r.status == ProviderStatus::NotFound)
+            .expect("at least one result");
+    };
+
+    let mut merged = ProviderResult {
+        usage: None,
+        data_source: None,
+        notes: Vec::new(),
+        breakdown: Vec::new(),
+        ..template
+    };
+    let mut total = UsageStats::default();
+    let mut sources: Vec<String> = Vec::new();
+    let mut home_slices: Vec<UsageBreakdown> = Vec::new();
+
+    for (root, result) in results {
+        let root_label = label(root.as_deref());
+        match result.usage {
+            Some(usage) => {
+                total.merge(&usage);
+                for slice in result.breakdown {
+                    add_slice(&mut merged.breakdown, slice);
+                }
+                sources.extend(result.data_source);
+                merged.notes.extend(result.notes.into_iter().map(|n| format!("{}: {}", root_label, n)));
+                home_slices.push(UsageBreakdown { dimension: HOME.to_string(), label: root_label, usage });
+            }
+            None if matches!(result.status, ProviderStatus::Error | ProviderStatus::Timeout) => {
+                let reason = result.error.unwrap_or_else(|| result.status.to_string());
+                merged.notes.push(format!("{}: {}", root_label, reason));
+            }
+            None => {}
+        }
+    }
+
+    merged.usage = Some(total);
+    merged.data_source = Some(sources.join(", "));
+    merged.breakdown.extend(home_slices);
+    merged
+}
@@ -0,0 +1,216 @@
+//! Shared HTTP client construction and report pagination for API-based providers
+
+use chrono::{DateTime, TimeZone, Utc};
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer};
+use std::time::Duration;
+
+/// Maximum time to establish a connection to an API host
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum time to wait between reads once connected
+pub const READ_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Build an HTTP client with connect and read timeouts.
+/// Falls back to a default client if the builder fails (e.g., TLS backend unavailable).
+pub fn client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .read_timeout(READ_TIMEOUT)
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+/// GET `url` with a bearer token and decode the JSON response.
+/// Errors are short descriptions suitable for a provider's error message.
+pub async fn get_json<T: DeserializeOwned>(url: &str, token: &str) -> Result<T, String> {
+    let response = client()
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Accept", "application/json")
+        .send()
+        .await
+        .map_err(|_| "network error".to_string())?;
+
+    match response.status() {
+        StatusCode::OK => {},
+        StatusCode::UNAUTHORIZED => return Err("invalid API key".to_string()),
+        StatusCode::FORBIDDEN => return Err("API key lacks permission".to_string()),
+        StatusCode::TOO_MANY_REQUESTS => return Err("rate limited".to_string()),
+        status => return Err(format!("HTTP {}", status)),
+    }
+
+    response.json().await.map_err(|_| "unexpected response format".to_string())
+}
+
+/// Why a paginated report couldn't be fetched
+#[derive(Debug)]
+pub enum FetchError {
+    Forbidden,
+    Unauthorized,
+    NotFound,
+    RateLimited,
+    Network,
+    Parse,
+}
+
+/// One page of a report of time buckets
+#[derive(Debug, Deserialize)]
+struct Page<T> {
+    #[serde(default = "Vec::new")]
+    data: Vec<Bucket<T>>,
+    #[serde(default)]
+    has_more: bool,
+    next_page: Option<String>,
+}
+
+/// A time bucket of a usage or cost report. OpenAI bounds buckets with Unix seconds
+/// (`start_time`), Anthropic with RFC 3339 timestamps (`starting_at`).
+#[derive(Debug, Deserialize)]
+pub struct Bucket<T> {
+    #[serde(rename = "start_time", alias = "starting_at", deserialize_with = "bucket_bound")]
+    start: DateTime<Utc>,
+    #[serde(rename = "end_time", alias = "ending_at", deserialize_with = "bucket_bound")]
+    end: DateTime<Utc>,
+    #[serde(default = "Vec::new")]
+    pub results: Vec<T>,
+}
+
+impl<T> Bucket<T> {
+    /// Time the bucket is counted at: its last covered moment, capped at now. Buckets are UTC
+    /// days, so this keeps the current day inside "today" whatever the report timezone.
+    pub fn time(&self) -> DateTime<Utc> {
+        let end = self.end.max(self.start + chrono::Duration::seconds(1)).min(Utc::now());
+        end - chrono::Duration::seconds(1)
+    }
+}
+
+fn bucket_bound<'de, D: Deserializer<'de>>(deserializer: D) -> Result<DateTime<Utc>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Bound {
+        Unix(i64),
+        Rfc3339(String),
+    }
+
+    match Bound::deserialize(deserializer)? {
+        Bound::Unix(seconds) => Utc.timestamp_opt(seconds, 0).single(),
+        Bound::Rfc3339(s) => DateTime::parse_from_rfc3339(&s).ok().map(|t| t.with_timezone(&Utc)),
+    }
+    .ok_or_else(|| serde::de::Error::custom("invalid bucket time"))
+}
+
+/// Fetch every bucket of a paginated report, following `next_page`. `request` builds the
+/// request for the first page; later pages add the `page` cursor to it.
+pub async fn fetch_buckets<T: DeserializeOwned>(
+    request: impl Fn() -> reqwest::RequestBuilder,
+) -> Result<Vec<Bucket<T>>, FetchError> {
+    let mut buckets = Vec::new();
+    let mut page_cursor: Option<String> = None;
+
+    loop {
+        let mut page_request = request();
+        if let Some(ref cursor) = page_cursor {
+            page_request = page_request.query(&[("page", cursor)]);
+        }
+
+        let response = page_request.send().await.map_err(|_| FetchError::Network)?;
+
+        match response.status() {
+            StatusCode::OK => {},
+            StatusCode::FORBIDDEN => return Err(FetchError::Forbidden),
+            StatusCode::UNAUTHORIZED => return Err(FetchError::Unauthorized),
+            StatusCode::NOT_FOUND => return Err(FetchError::NotFound),
+            StatusCode::TOO_MANY_REQUESTS => return Err(FetchError::RateLimited),
+            _ => return Err(FetchError::Network),
+        }
+
+        let page: Page<T> = response.json().await.map_err(|_| FetchError::Parse)?;
+        buckets.extend(page.data);
+
+        match page.next_page {
+            Some(cursor) if page.has_more => page_cursor = Some(cursor),
+            _ => break,
+        }
+    }
+
+    Ok(buckets)
+}
+
+/// A local stand-in for an API host in tests
+#[cfg(test)]
+pub mod test_server {
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+
+    pub struct TestServer {
+        pub url: String,
+        requests: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl TestServer {
+        /// Request lines received so far, e.g. `GET /v1/costs?limit=31 HTTP/1.1`
+        pub fn requests(&self) -> Vec<String> {
+            self.requests.lock().unwrap().clone()
+        }
+    }
+
+    /// Answer each request with the JSON body of the first route whose pattern occurs in
+    /// its request line, or a 404
+    pub fn serve(routes: Vec<(&'static str, String)>) -> TestServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = Arc::clone(&requests);
+
+        thread::spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                let _ = reader.read_line(&mut request_line);
+                // Skip the headers; requests have no body
+                let mut header = String::new();
+                while reader.read_line(&mut header).map(|n| n > 2).unwrap_or(false) {
+                    header.clear();
+                }
+
+                let request_line = request_line.trim_end().to_string();
+                let response = match routes.iter().find(|(pattern, _)| request_line.contains(pattern)) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    ),
+                    None => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+                };
+                log.lock().unwrap().push(request_line);
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        TestServer { url, requests }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn follows_pages_of_unix_and_rfc3339_buckets() {
+        let first = r#"{"data":[{"start_time":1750032000,"end_time":1750118400,"results":[1]}],"has_more":true,"next_page":"p2"}"#;
+        let second = r#"{"data":[{"starting_at":"2025-06-17T00:00:00Z","ending_at":"2025-06-18T00:00:00Z","results":[2,3]}],"has_more":false}"#;
+        let server = test_server::serve(vec![("page=p2", second.to_string()), ("/report", first.to_string())]);
+
+        let client = client();
+        let buckets = fetch_buckets::<u32>(|| client.get(format!("{}/report", server.url))).await.unwrap();
+
+        assert_eq!(buckets.iter().flat_map(|b| b.results.clone()).collect::<Vec<_>>(), vec![1, 2, 3]);
+        assert_eq!(buckets[0].time().to_rfc3339(), "2025-06-16T23:59:59+00:00");
+        assert_eq!(buckets[1].time().to_rfc3339(), "2025-06-17T23:59:59+00:00");
+        assert_eq!(server.requests().len(), 2);
+    }
+}
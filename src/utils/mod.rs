@@ -1,7 +1,12 @@
 //! Utility modules
 
+pub mod allocation;
+pub mod config;
 pub mod db;
 pub mod format;
+pub mod git;
+pub mod homes;
+pub mod http;
 pub mod paths;
 pub mod time;
 pub mod tokenizer;
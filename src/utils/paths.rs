@@ -1,10 +1,92 @@
 //! Cross-platform path utilities for all AI tool data sources
 
-use std::path::PathBuf;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+
+tokio::task_local! {
+    /// Alternative home root (devcontainer, WSL mount, backup) the current task resolves paths under
+    static HOME_ROOT: PathBuf;
+}
+
+/// The alternative home root in effect for the current task, if any
+pub fn alternate_home() -> Option<PathBuf> {
+    HOME_ROOT.try_with(|home| home.clone()).ok()
+}
+
+/// Run `f` with every path resolving under `home` instead of the user's home directory.
+/// Environment overrides (`CLAUDE_CONFIG_DIR`, `XDG_CONFIG_HOME`) only apply to the real home.
+pub async fn with_home<F: Future>(home: PathBuf, f: F) -> F::Output {
+    HOME_ROOT.scope(home, f).await
+}
+
+/// Synchronous form of [`with_home`]
+pub fn with_home_sync<R>(home: PathBuf, f: impl FnOnce() -> R) -> R {
+    HOME_ROOT.sync_scope(home, f)
+}
 
 /// Get home directory
 pub fn home_dir() -> Option<PathBuf> {
-    dirs::home_dir()
+    alternate_home().or_else(dirs::home_dir)
+}
+
+/// Application data directory of a home copied from another OS (Windows or macOS),
+/// detected from its contents so e.g. `/mnt/c/Users/me` resolves like a Windows home
+fn foreign_app_dir(home: &Path) -> Option<PathBuf> {
+    [
+        home.join("AppData").join("Roaming"),
+        home.join("Library").join("Application Support"),
+    ]
+    .into_iter()
+    .find(|dir| dir.is_dir())
+}
+
+/// Application directory under an alternative home, using this platform's layout
+/// unless the home comes from another OS. `xdg` is the location relative to the home on Linux.
+fn alternate_app_dir(home: &Path, xdg: &str) -> PathBuf {
+    foreign_app_dir(home).unwrap_or_else(|| {
+        if cfg!(target_os = "windows") {
+            home.join("AppData").join("Roaming")
+        } else if cfg!(target_os = "macos") {
+            home.join("Library").join("Application Support")
+        } else {
+            home.join(xdg)
+        }
+    })
+}
+
+/// Roaming data directory (`dirs::data_dir` layout), under the alternative home if one is set
+fn data_dir() -> Option<PathBuf> {
+    match alternate_home() {
+        Some(home) => Some(alternate_app_dir(&home, ".local/share")),
+        None => dirs::data_dir(),
+    }
+}
+
+/// `$XDG_CONFIG_HOME`, defaulting to `~/.config`
+fn xdg_config_home() -> Option<PathBuf> {
+    if alternate_home().is_none() {
+        if let Some(dir) = std::env::var_os("XDG_CONFIG_HOME").map(PathBuf::from).filter(|p| p.is_absolute()) {
+            return Some(dir);
+        }
+    }
+    home_dir().map(|h| h.join(".config"))
+}
+
+/// Expand a leading `~/` to the home directory
+pub fn expand_home(path: &str) -> String {
+    match (path.strip_prefix("~/"), home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+/// Comma-separated display form of several data paths
+pub fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|p| p.to_string_lossy().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
 }
 
 /// Get application data directory
@@ -12,34 +94,92 @@ pub fn home_dir() -> Option<PathBuf> {
 pub fn app_data_dir() -> Option<PathBuf> {
     #[cfg(target_os = "windows")]
     {
-        dirs::data_dir()
+        data_dir()
     }
     #[cfg(target_os = "macos")]
     {
-        dirs::data_dir() // ~/Library/Application Support
+        data_dir() // ~/Library/Application Support
     }
     #[cfg(target_os = "linux")]
     {
-        dirs::data_local_dir() // ~/.local/share
+        data_dir() // ~/.local/share
     }
     #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
     {
-        dirs::data_dir()
+        data_dir()
     }
 }
 
 /// Get config directory
-#[allow(dead_code)]
 pub fn config_dir() -> Option<PathBuf> {
-    dirs::config_dir()
+    match alternate_home() {
+        Some(home) => Some(alternate_app_dir(&home, ".config")),
+        None => dirs::config_dir(),
+    }
+}
+
+/// Path configurations for a2zusage's own files
+pub mod a2zusage {
+    use super::*;
+
+    /// `$XDG_CONFIG_HOME/a2zusage`, defaulting to `~/.config/a2zusage` on every platform
+    pub fn config_dir() -> Option<PathBuf> {
+        xdg_config_home().map(|d| d.join("a2zusage"))
+    }
+
+    /// User configuration: provider toggles, extra data paths, API base URLs and display defaults
+    pub fn config_file() -> Option<PathBuf> {
+        config_dir().map(|d| d.join("config.toml"))
+    }
+
+    /// Cost allocation rules mapping paths, remotes and models to cost-center labels
+    pub fn cost_rules_file() -> Option<PathBuf> {
+        config_dir().map(|d| d.join("cost-centers.toml"))
+    }
 }
 
 /// Path configurations for Claude Code
 pub mod claude_code {
     use super::*;
 
-    pub fn projects_dir() -> Option<PathBuf> {
-        home_dir().map(|h| h.join(".claude").join("projects"))
+    /// Environment variable holding one or more comma-separated Claude config directories
+    pub const CONFIG_DIR_ENV: &str = "CLAUDE_CONFIG_DIR";
+
+    /// Claude config roots, in priority order: `CLAUDE_CONFIG_DIR` entries,
+    /// the XDG location (`$XDG_CONFIG_HOME/claude`, default `~/.config/claude`), then `~/.claude`.
+    /// Duplicates (including symlinked aliases) are removed.
+    pub fn config_roots() -> Vec<PathBuf> {
+        let mut roots: Vec<PathBuf> = Vec::new();
+
+        // The shell only expands a leading `~`, not one after a comma
+        if let (Ok(value), None) = (std::env::var(CONFIG_DIR_ENV), alternate_home()) {
+            roots.extend(
+                value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|s| !s.is_empty())
+                    .map(|s| PathBuf::from(expand_home(s))),
+            );
+        }
+
+        if let Some(xdg) = xdg_config_home() {
+            roots.push(xdg.join("claude"));
+        }
+
+        if let Some(h) = home_dir() {
+            roots.push(h.join(".claude"));
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        roots
+            .into_iter()
+            .filter(|root| seen.insert(root.canonicalize().unwrap_or_else(|_| root.clone())))
+            .collect()
+    }
+
+    /// All candidate `projects` directories (existing or not)
+    pub fn projects_dirs() -> Vec<PathBuf> {
+        config_roots().into_iter().map(|r| r.join("projects")).collect()
     }
 
     pub fn config_file() -> Option<PathBuf> {
@@ -52,6 +192,127 @@ pub mod claude_code {
     }
 }
 
+/// Path configurations for VS Code and the editors built on it, which share its user-data layout
+pub mod vscode {
+    use super::*;
+
+    /// A location inside one editor's user-data directory, labeled with the editor's name
+    pub type EditorPath = (&'static str, PathBuf);
+
+    /// (label, user-data directory name) for each VS Code family editor
+    pub const EDITORS: &[(&str, &str)] = &[
+        ("VS Code", "Code"),
+        ("VS Code Insiders", "Code - Insiders"),
+        ("VSCodium", "VSCodium"),
+        ("Cursor", "Cursor"),
+        ("Windsurf", "Windsurf"),
+        ("Positron", "Positron"),
+    ];
+
+    fn app_dir() -> Option<PathBuf> {
+        #[cfg(any(target_os = "windows", target_os = "macos"))]
+        {
+            data_dir()
+        }
+        #[cfg(target_os = "linux")]
+        {
+            config_dir()
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+        {
+            None
+        }
+    }
+
+    /// Every editor's user-data directory (existing or not)
+    pub fn user_data_dirs() -> Vec<EditorPath> {
+        let Some(app_dir) = app_dir() else {
+            return Vec::new();
+        };
+        EDITORS
+            .iter()
+            .map(|(label, dir)| (*label, app_dir.join(dir)))
+            .collect()
+    }
+
+    /// `User/globalStorage` of every editor
+    pub fn global_storage_dirs() -> Vec<EditorPath> {
+        user_data_dirs()
+            .into_iter()
+            .map(|(label, dir)| (label, dir.join("User").join("globalStorage")))
+            .collect()
+    }
+
+    /// An extension's global storage directory in every editor. VS Code names the
+    /// directory after the lowercased extension ID, whatever the casing in the marketplace.
+    pub fn extension_storage(extension_id: &str) -> Vec<EditorPath> {
+        let extension_id = extension_id.to_lowercase();
+        global_storage_dirs()
+            .into_iter()
+            .map(|(label, dir)| (label, dir.join(&extension_id)))
+            .collect()
+    }
+
+    /// Drop locations inside editors that aren't installed (no user-data directory).
+    /// Keeps the first location if nothing is installed, so `doctor` still shows where it looked.
+    pub fn installed(paths: Vec<EditorPath>) -> Vec<EditorPath> {
+        let missing: Vec<PathBuf> = user_data_dirs()
+            .into_iter()
+            .map(|(_, dir)| dir)
+            .filter(|dir| !dir.exists())
+            .collect();
+        let (kept, dropped): (Vec<EditorPath>, Vec<EditorPath>) = paths
+            .into_iter()
+            .partition(|(_, path)| !missing.iter().any(|dir| path.starts_with(dir)));
+
+        if kept.is_empty() {
+            dropped.into_iter().take(1).collect()
+        } else {
+            kept
+        }
+    }
+
+    /// Log directories of every editor
+    pub fn logs_dirs() -> Vec<EditorPath> {
+        user_data_dirs()
+            .into_iter()
+            .map(|(label, dir)| (label, dir.join("logs")))
+            .collect()
+    }
+
+    /// Local path of a workspace `folder` URI (`file:///home/me/my%20project`).
+    /// Remote workspaces (`vscode-remote://…`) have no local path.
+    pub fn folder_path(uri: &str) -> Option<PathBuf> {
+        let path = uri.strip_prefix("file://")?;
+        let bytes = path.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let escaped = (bytes[i] == b'%')
+                .then(|| path.get(i + 1..i + 3))
+                .flatten()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match escaped {
+                Some(byte) => {
+                    decoded.push(byte);
+                    i += 3;
+                }
+                None => {
+                    decoded.push(bytes[i]);
+                    i += 1;
+                }
+            }
+        }
+        let path = String::from_utf8(decoded).ok()?;
+        // Windows drives come through as /c:/Users/…
+        let path = match path.as_bytes() {
+            [b'/', _, b':', ..] if cfg!(windows) => &path[1..],
+            _ => &path,
+        };
+        Some(PathBuf::from(path))
+    }
+}
+
 /// Path configurations for Cursor
 pub mod cursor {
     use super::*;
@@ -59,11 +320,11 @@ pub mod cursor {
     pub fn global_storage() -> Option<PathBuf> {
         #[cfg(target_os = "windows")]
         {
-            dirs::data_dir().map(|d| d.join("Cursor").join("User").join("globalStorage").join("state.vscdb"))
+            data_dir().map(|d| d.join("Cursor").join("User").join("globalStorage").join("state.vscdb"))
         }
         #[cfg(target_os = "macos")]
         {
-            dirs::data_dir().map(|d| d.join("Cursor").join("User").join("globalStorage").join("state.vscdb"))
+            data_dir().map(|d| d.join("Cursor").join("User").join("globalStorage").join("state.vscdb"))
         }
         #[cfg(target_os = "linux")]
         {
@@ -78,11 +339,11 @@ pub mod cursor {
     pub fn workspace_storage() -> Option<PathBuf> {
         #[cfg(target_os = "windows")]
         {
-            dirs::data_dir().map(|d| d.join("Cursor").join("User").join("workspaceStorage"))
+            data_dir().map(|d| d.join("Cursor").join("User").join("workspaceStorage"))
         }
         #[cfg(target_os = "macos")]
         {
-            dirs::data_dir().map(|d| d.join("Cursor").join("User").join("workspaceStorage"))
+            data_dir().map(|d| d.join("Cursor").join("User").join("workspaceStorage"))
         }
         #[cfg(target_os = "linux")]
         {
@@ -103,23 +364,9 @@ pub mod github_copilot {
         home_dir().map(|h| h.join(".config").join("github-copilot").join("hosts.json"))
     }
 
-    pub fn vscode_logs() -> Option<PathBuf> {
-        #[cfg(target_os = "windows")]
-        {
-            dirs::data_dir().map(|d| d.join("Code").join("logs"))
-        }
-        #[cfg(target_os = "macos")]
-        {
-            dirs::data_dir().map(|d| d.join("Code").join("logs"))
-        }
-        #[cfg(target_os = "linux")]
-        {
-            config_dir().map(|d| d.join("Code").join("logs"))
-        }
-        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-        {
-            None
-        }
+    /// Log directories of every VS Code family editor (Copilot logs live under each one)
+    pub fn vscode_logs() -> Vec<vscode::EditorPath> {
+        vscode::logs_dirs()
     }
 }
 
@@ -147,7 +394,7 @@ pub mod opencode {
     pub fn storage_dir() -> Option<PathBuf> {
         #[cfg(target_os = "windows")]
         {
-            dirs::data_dir().map(|d| d.join("opencode").join("storage").join("message"))
+            data_dir().map(|d| d.join("opencode").join("storage").join("message"))
         }
         #[cfg(not(target_os = "windows"))]
         {
@@ -156,6 +403,182 @@ pub mod opencode {
     }
 }
 
+/// Path configurations for the OpenAI Codex CLI
+pub mod codex_cli {
+    use super::*;
+
+    /// Environment variable overriding the Codex home directory
+    pub const HOME_ENV: &str = "CODEX_HOME";
+
+    /// `$CODEX_HOME`, or `~/.codex`
+    pub fn codex_home() -> Option<PathBuf> {
+        match (std::env::var_os(HOME_ENV), alternate_home()) {
+            (Some(dir), None) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            _ => home_dir().map(|h| h.join(".codex")),
+        }
+    }
+
+    /// Rollout transcripts, `sessions/YYYY/MM/DD/rollout-*.jsonl`
+    pub fn sessions_dir() -> Option<PathBuf> {
+        codex_home().map(|d| d.join("sessions"))
+    }
+
+    /// Rollouts of archived sessions (same format, flat directory)
+    pub fn archived_sessions_dir() -> Option<PathBuf> {
+        codex_home().map(|d| d.join("archived_sessions"))
+    }
+}
+
+/// Path configurations for Aider
+pub mod aider {
+    use super::*;
+
+    /// Environment variable equivalent of Aider's `--analytics-log`
+    pub const ANALYTICS_LOG_ENV: &str = "AIDER_ANALYTICS_LOG";
+
+    /// Chat transcript Aider writes in the repository it runs in
+    pub const HISTORY_FILE: &str = ".aider.chat.history.md";
+
+    /// Directories under home where repositories usually live
+    const CODE_DIRS: &[&str] = &["code", "src", "projects", "dev", "repos", "work", "git", "workspace"];
+
+    pub fn config_file() -> Option<PathBuf> {
+        home_dir().map(|h| h.join(".aider.conf.yml"))
+    }
+
+    /// Analytics log named by `AIDER_ANALYTICS_LOG` (ignored under an alternative home root)
+    pub fn analytics_log_from_env() -> Option<PathBuf> {
+        match (std::env::var_os(ANALYTICS_LOG_ENV), alternate_home()) {
+            (Some(path), None) if !path.is_empty() => Some(PathBuf::from(path)),
+            _ => None,
+        }
+    }
+
+    /// Chat history files in known repository locations: home, the current repository,
+    /// and repositories one or two levels below common code directories
+    pub fn history_files() -> Vec<PathBuf> {
+        let Some(home) = home_dir() else {
+            return Vec::new();
+        };
+        let mut files = vec![home.join(HISTORY_FILE)];
+
+        if alternate_home().is_none() {
+            if let Some(repo) = std::env::current_dir().ok().and_then(|cwd| crate::utils::git::find_repo_root(&cwd)) {
+                files.push(repo.join(HISTORY_FILE));
+            }
+        }
+
+        for dir in CODE_DIRS {
+            for depth in ["*", "*/*"] {
+                let pattern = home.join(dir).join(depth).join(HISTORY_FILE);
+                if let Ok(paths) = glob::glob(&pattern.to_string_lossy()) {
+                    files.extend(paths.flatten());
+                }
+            }
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        files.retain(|f| seen.insert(f.clone()));
+        files
+    }
+}
+
+/// Path configurations for Continue
+pub mod continue_dev {
+    use super::*;
+
+    /// Environment variable overriding Continue's global directory
+    pub const HOME_ENV: &str = "CONTINUE_GLOBAL_DIR";
+
+    /// `$CONTINUE_GLOBAL_DIR`, or `~/.continue`
+    pub fn global_dir() -> Option<PathBuf> {
+        match (std::env::var_os(HOME_ENV), alternate_home()) {
+            (Some(dir), None) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            _ => home_dir().map(|h| h.join(".continue")),
+        }
+    }
+
+    /// Development data, one `<event>.jsonl` per event type under a schema version directory
+    pub fn dev_data_dir() -> Option<PathBuf> {
+        global_dir().map(|d| d.join("dev_data"))
+    }
+}
+
+/// Path configurations for the Zed editor
+pub mod zed {
+    use super::*;
+
+    /// Agent panel threads database (`threads/threads.db` in Zed's data directory)
+    pub fn threads_db() -> Option<PathBuf> {
+        #[cfg(target_os = "macos")]
+        {
+            data_dir().map(|d| d.join("Zed").join("threads").join("threads.db"))
+        }
+        #[cfg(target_os = "windows")]
+        {
+            let local = match alternate_home() {
+                Some(home) => Some(home.join("AppData").join("Local")),
+                None => dirs::data_local_dir(),
+            };
+            local.map(|d| d.join("Zed").join("threads").join("threads.db"))
+        }
+        #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+        {
+            data_dir().map(|d| d.join("zed").join("threads").join("threads.db"))
+        }
+    }
+}
+
+/// Path configurations for Block's Goose agent
+pub mod goose {
+    use super::*;
+
+    /// Session files, one `<id>.jsonl` per session
+    pub fn sessions_dir() -> Option<PathBuf> {
+        #[cfg(target_os = "windows")]
+        {
+            data_dir().map(|d| d.join("Block").join("goose").join("data").join("sessions"))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            home_dir().map(|h| h.join(".local").join("share").join("goose").join("sessions"))
+        }
+    }
+
+    /// Goose settings (`GOOSE_PROVIDER`, `GOOSE_MODEL`, extensions)
+    pub fn config_file() -> Option<PathBuf> {
+        #[cfg(target_os = "windows")]
+        {
+            data_dir().map(|d| d.join("Block").join("goose").join("config").join("config.yaml"))
+        }
+        #[cfg(not(target_os = "windows"))]
+        {
+            xdg_config_home().map(|d| d.join("goose").join("config.yaml"))
+        }
+    }
+}
+
+/// Path configurations for Simon Willison's `llm` CLI
+pub mod llm {
+    use super::*;
+
+    /// Environment variable overriding llm's user directory
+    pub const USER_PATH_ENV: &str = "LLM_USER_PATH";
+
+    /// `$LLM_USER_PATH`, or the `io.datasette.llm` app directory in the config directory
+    pub fn user_dir() -> Option<PathBuf> {
+        match (std::env::var_os(USER_PATH_ENV), alternate_home()) {
+            (Some(dir), None) if !dir.is_empty() => Some(PathBuf::from(dir)),
+            _ => config_dir().map(|d| d.join("io.datasette.llm")),
+        }
+    }
+
+    /// Prompt/response log database
+    pub fn logs_db() -> Option<PathBuf> {
+        user_dir().map(|d| d.join("logs.db"))
+    }
+}
+
 /// Path configurations for Amazon Q Developer
 pub mod amazon_q {
     use super::*;
@@ -204,11 +627,11 @@ pub mod tabnine {
     pub fn logs_dir() -> Option<PathBuf> {
         #[cfg(target_os = "windows")]
         {
-            dirs::data_dir().map(|d| d.join("TabNine").join("logs"))
+            data_dir().map(|d| d.join("TabNine").join("logs"))
         }
         #[cfg(target_os = "macos")]
         {
-            dirs::data_dir().map(|d| d.join("TabNine").join("logs"))
+            data_dir().map(|d| d.join("TabNine").join("logs"))
         }
         #[cfg(target_os = "linux")]
         {
@@ -225,31 +648,32 @@ pub mod tabnine {
 pub mod cline {
     use super::*;
 
-    fn vscode_global_storage() -> Option<PathBuf> {
-        #[cfg(target_os = "windows")]
-        {
-            dirs::data_dir().map(|d| d.join("Code").join("User").join("globalStorage"))
-        }
-        #[cfg(target_os = "macos")]
-        {
-            dirs::data_dir().map(|d| d.join("Code").join("User").join("globalStorage"))
-        }
-        #[cfg(target_os = "linux")]
-        {
-            config_dir().map(|d| d.join("Code").join("User").join("globalStorage"))
-        }
-        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-        {
-            None
-        }
+    /// Known Cline-family extensions, which share Cline's task storage format: (fork name, extension ID)
+    pub const FORKS: &[(&str, &str)] = &[
+        ("Cline", "saoudrizwan.claude-dev"),
+        ("Roo Code", "rooveterinary.roo-cline"),
+        ("Kilo Code", "kilocode.kilo-code"),
+    ];
+
+    /// Task directories of one extension in every VS Code family editor
+    pub fn tasks_dirs(extension_id: &str) -> Vec<vscode::EditorPath> {
+        vscode::extension_storage(extension_id)
+            .into_iter()
+            .map(|(label, dir)| (label, dir.join("tasks")))
+            .collect()
     }
 
-    pub fn original_tasks_dir() -> Option<PathBuf> {
-        vscode_global_storage().map(|d| d.join("saoudrizwan.claude-dev").join("tasks"))
-    }
-
-    pub fn roo_code_tasks_dir() -> Option<PathBuf> {
-        vscode_global_storage().map(|d| d.join("rooveterinary.roo-cline").join("tasks"))
+    /// The fork a task directory belongs to, from the extension ID in its path.
+    /// Extensions outside [`FORKS`] are named by their ID.
+    pub fn fork_name(tasks_dir: &Path, extension_ids: &[String]) -> Option<String> {
+        tasks_dir.components().find_map(|component| {
+            let component = component.as_os_str().to_string_lossy().to_lowercase();
+            FORKS
+                .iter()
+                .find(|(_, id)| *id == component)
+                .map(|(name, _)| name.to_string())
+                .or_else(|| extension_ids.iter().find(|id| id.to_lowercase() == component).cloned())
+        })
     }
 
     pub fn roo_usage_tracking() -> Option<PathBuf> {
@@ -261,23 +685,9 @@ pub mod cline {
 pub mod sourcegraph_cody {
     use super::*;
 
-    pub fn vscode_extension() -> Option<PathBuf> {
-        #[cfg(target_os = "windows")]
-        {
-            dirs::data_dir().map(|d| d.join("Code").join("User").join("globalStorage").join("sourcegraph.cody-ai"))
-        }
-        #[cfg(target_os = "macos")]
-        {
-            dirs::data_dir().map(|d| d.join("Code").join("User").join("globalStorage").join("sourcegraph.cody-ai"))
-        }
-        #[cfg(target_os = "linux")]
-        {
-            config_dir().map(|d| d.join("Code").join("User").join("globalStorage").join("sourcegraph.cody-ai"))
-        }
-        #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
-        {
-            None
-        }
+    /// Cody's extension storage in every VS Code family editor
+    pub fn vscode_extensions() -> Vec<vscode::EditorPath> {
+        vscode::extension_storage("sourcegraph.cody-ai")
     }
 }
 
@@ -300,7 +710,7 @@ pub mod warp {
         }
         #[cfg(target_os = "windows")]
         {
-            dirs::data_dir().map(|d| d.join("Warp").join("warp.sqlite"))
+            data_dir().map(|d| d.join("Warp").join("warp.sqlite"))
         }
         #[cfg(target_os = "linux")]
         {
@@ -319,7 +729,7 @@ pub mod warp {
         }
         #[cfg(target_os = "windows")]
         {
-            dirs::data_dir().map(|d| d.join("Warp").join("logs"))
+            data_dir().map(|d| d.join("Warp").join("logs"))
         }
         #[cfg(target_os = "linux")]
         {
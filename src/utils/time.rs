@@ -1,31 +1,85 @@
 //! Time utilities for consistent time range calculations across all providers
 
 use crate::types::TimeRange;
-use chrono::{Datelike, Local, TimeZone, Utc};
+use crate::utils::config;
+use chrono::{DateTime, Datelike, FixedOffset, Local, TimeZone, Utc};
+use std::str::FromStr;
 
-/// Get time ranges for today, this week, and this month using the user's local timezone.
-/// This ensures that "today" matches the user's local day boundaries, not UTC.
+/// Timezone whose day, week and month boundaries the report uses
+#[derive(Debug, Clone, Copy, Default)]
+pub enum ReportTimeZone {
+    #[default]
+    Local,
+    Utc,
+    Fixed(FixedOffset),
+}
+
+impl FromStr for ReportTimeZone {
+    type Err = anyhow::Error;
+
+    /// Accepts `local`, `utc` or a UTC offset such as `+05:30` / `-0800`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "local" => Ok(Self::Local),
+            "utc" | "z" => Ok(Self::Utc),
+            other => {
+                let (sign, digits) = match other.split_at_checked(1) {
+                    Some(("+", rest)) => (1, rest),
+                    Some(("-", rest)) => (-1, rest),
+                    _ => anyhow::bail!("invalid timezone {:?} (expected local, utc or an offset like +05:30)", s),
+                };
+                let digits = digits.replace(':', "");
+                let (hours, minutes) = match digits.len() {
+                    2 => (digits.parse::<i32>().ok(), Some(0)),
+                    4 => (digits[..2].parse::<i32>().ok(), digits[2..].parse::<i32>().ok()),
+                    _ => (None, None),
+                };
+                hours
+                    .zip(minutes)
+                    .filter(|&(h, m)| h <= 14 && m < 60)
+                    .and_then(|(h, m)| FixedOffset::east_opt(sign * (h * 3600 + m * 60)))
+                    .map(Self::Fixed)
+                    .ok_or_else(|| anyhow::anyhow!("invalid timezone offset {:?}", s))
+            }
+        }
+    }
+}
+
+/// Get time ranges for today, this week, and this month in the configured report timezone
+/// (the user's local timezone by default).
+/// This ensures that "today" matches the user's day boundaries, not UTC.
 ///
 /// Returns: (today_range, this_week_range, this_month_range)
 pub fn get_local_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
-    // Use local timezone for calculating "today", "this week", "this month"
-    // This ensures the user's local day boundaries are respected
-    let now_local = Local::now();
+    match config::get().timezone() {
+        ReportTimeZone::Local => time_ranges_in(&Local),
+        ReportTimeZone::Utc => time_ranges_in(&Utc),
+        ReportTimeZone::Fixed(offset) => time_ranges_in(&offset),
+    }
+}
+
+/// Earliest moment covered by the week and month ranges; the week can start in the previous month
+pub fn ranges_start(ranges: &(TimeRange, TimeRange, TimeRange)) -> DateTime<Utc> {
+    ranges.1.start.min(ranges.2.start)
+}
+
+fn time_ranges_in<Tz: TimeZone>(tz: &Tz) -> (TimeRange, TimeRange, TimeRange) {
+    let now_local = Utc::now().with_timezone(tz);
     let now_utc = Utc::now();
 
-    // Today's start in local timezone, converted to UTC for comparison
-    let today_start_local = Local
+    // Today's start in the report timezone, converted to UTC for comparison
+    let today_start_local = tz
         .with_ymd_and_hms(now_local.year(), now_local.month(), now_local.day(), 0, 0, 0)
         .unwrap();
     let today_start = today_start_local.with_timezone(&Utc);
 
-    // Week start (Sunday) in local timezone, converted to UTC
-    let week_start_local = today_start_local
+    // Week start (Sunday) in the report timezone, converted to UTC
+    let week_start_local = today_start_local.clone()
         - chrono::Duration::days(now_local.weekday().num_days_from_sunday() as i64);
     let week_start = week_start_local.with_timezone(&Utc);
 
-    // Month start in local timezone, converted to UTC
-    let month_start_local = Local
+    // Month start in the report timezone, converted to UTC
+    let month_start_local = tz
         .with_ymd_and_hms(now_local.year(), now_local.month(), 1, 0, 0, 0)
         .unwrap();
     let month_start = month_start_local.with_timezone(&Utc);
@@ -46,3 +100,36 @@ pub fn get_local_time_ranges() -> (TimeRange, TimeRange, TimeRange) {
     )
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset_seconds(s: &str) -> Option<i32> {
+        match s.parse::<ReportTimeZone>() {
+            Ok(ReportTimeZone::Fixed(offset)) => Some(offset.local_minus_utc()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn parses_named_timezones() {
+        assert!(matches!("local".parse::<ReportTimeZone>(), Ok(ReportTimeZone::Local)));
+        assert!(matches!(" UTC ".parse::<ReportTimeZone>(), Ok(ReportTimeZone::Utc)));
+        assert!(matches!("Z".parse::<ReportTimeZone>(), Ok(ReportTimeZone::Utc)));
+    }
+
+    #[test]
+    fn parses_offsets_with_and_without_colon() {
+        assert_eq!(offset_seconds("+05:30"), Some(5 * 3600 + 30 * 60));
+        assert_eq!(offset_seconds("-0800"), Some(-8 * 3600));
+        assert_eq!(offset_seconds("+09"), Some(9 * 3600));
+        assert_eq!(offset_seconds("+14:00"), Some(14 * 3600));
+    }
+
+    #[test]
+    fn rejects_malformed_offsets() {
+        for s in ["05:30", "+5:30", "+15:00", "+05:60", "+05:3", "+ab:cd", "", "Europe/Berlin"] {
+            assert!(s.parse::<ReportTimeZone>().is_err(), "{:?} should be rejected", s);
+        }
+    }
+}
@@ -24,6 +24,11 @@ static MODEL_PRICING: Lazy<HashMap<&'static str, (f64, f64)>> = Lazy::new(|| {
     m.insert("gpt-3.5-turbo", (0.5, 1.5));
     m.insert("o1", (15.0, 60.0));
     m.insert("o1-mini", (3.0, 12.0));
+    m.insert("o3", (2.0, 8.0));
+    m.insert("o4-mini", (1.1, 4.4));
+    m.insert("gpt-5", (1.25, 10.0));
+    m.insert("gpt-5-mini", (0.25, 2.0));
+    m.insert("codex-mini", (1.5, 6.0));
 
     // Google models
     m.insert("gemini-pro", (0.5, 1.5));
@@ -39,6 +44,7 @@ static MODEL_PRICING: Lazy<HashMap<&'static str, (f64, f64)>> = Lazy::new(|| {
 
 /// Estimate token count from character count
 /// Rule of thumb: ~4 characters per token for English text/code
+#[allow(dead_code)]
 pub fn estimate_tokens_from_chars(chars: usize) -> u64 {
     (chars / 4) as u64
 }
@@ -76,11 +82,21 @@ fn find_model_pricing(model: Option<&str>) -> (f64, f64) {
         return *pricing;
     }
 
-    // Try partial match
-    for (key, pricing) in MODEL_PRICING.iter() {
-        if model.contains(key) || key.contains(&model.as_str()) {
-            return *pricing;
-        }
+    // Try partial match: the longest key in the name ("gpt-5-mini-2025-08-07" is gpt-5-mini,
+    // not gpt-5), else the shortest key containing the name. Ties go to the first key
+    // alphabetically, so the result doesn't depend on hash order.
+    let contained = MODEL_PRICING
+        .iter()
+        .filter(|(key, _)| model.contains(*key))
+        .max_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| b.0.cmp(a.0)));
+    let containing = || {
+        MODEL_PRICING
+            .iter()
+            .filter(|(key, _)| key.contains(model.as_str()))
+            .min_by(|a, b| a.0.len().cmp(&b.0.len()).then_with(|| a.0.cmp(b.0)))
+    };
+    if let Some((_, pricing)) = contained.or_else(containing) {
+        return *pricing;
     }
 
     *MODEL_PRICING.get("default").unwrap()
@@ -125,3 +141,23 @@ pub fn calculate_cache_savings(cache_read_tokens: u64, model: Option<&str>) -> f
     let cache_cost = full_cost * 0.1; // Cache reads cost 10% of input
     full_cost - cache_cost
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dated_names_match_the_most_specific_model() {
+        assert_eq!(find_model_pricing(Some("gpt-5-mini-2025-08-07")), (0.25, 2.0));
+        assert_eq!(find_model_pricing(Some("gpt-5-2025-08-07")), (1.25, 10.0));
+        assert_eq!(find_model_pricing(Some("gpt-4o-mini-2024-07-18")), (0.15, 0.6));
+        assert_eq!(find_model_pricing(Some("o4-mini-2025-04-16")), (1.1, 4.4));
+        assert_eq!(find_model_pricing(Some("codex-mini-latest")), (1.5, 6.0));
+    }
+
+    #[test]
+    fn unknown_models_use_the_default_price() {
+        assert_eq!(find_model_pricing(Some("llama-3.1-70b")), (1.0, 3.0));
+        assert_eq!(find_model_pricing(None), (1.0, 3.0));
+    }
+}